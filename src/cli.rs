@@ -1,6 +1,7 @@
 use std::{
     fs::{self, File},
     path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Result;
@@ -14,13 +15,27 @@ use axl_lib::{
         },
     },
     error::Error,
-    fzf::FzfCmd,
-    tmux::TmuxCmd,
+    fzf::{FzfCmd, FZF_TMUX_VERSION},
+    helper::{command_line, glob_match, parse_duration},
+    history::SessionHistory,
+    multiplexer::{Multiplexer, SessionName},
+    record::{record_sink, recordings, RecordFormat},
+    shell_history::history_entries,
+    status::{cached_segment, GitStatus, StatusSegment},
+    tmux::{
+        prune::PruneCriteria,
+        version::TmuxFeature,
+        watch::{WatchAlert, WatchTarget},
+        TmuxCmd, TmuxSocket,
+    },
     zoxide::ZoxideCmd,
 };
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
 use clap_verbosity_flag::LogLevel;
 use colored::Colorize;
+use inquire::Confirm;
+use regex::Regex;
+use strum::IntoEnumIterator;
 use strum_macros::Display;
 use tracing::{debug, instrument, trace};
 
@@ -36,7 +51,7 @@ pub struct Cli {
     pub args: SharedArgs,
 
     #[clap(skip)]
-    ctx: AxlContext,
+    pub ctx: AxlContext,
 }
 
 impl Cli {
@@ -47,6 +62,13 @@ impl Cli {
         let axl_config: AxlConfig = AxlConfig::from_file(&self.ctx.config_path)?;
         self.ctx.config = axl_config;
         self.ctx.config.general.decoration = self.args.decoration.clone();
+        if let Some(on_error) = &self.args.on_error {
+            self.ctx.config.general.on_error = on_error.clone();
+        }
+        if let Some(socket) = &self.args.socket {
+            self.ctx.config.tmux.socket = Some(TmuxSocket(socket.clone()));
+        }
+        TmuxCmd::configure(&self.ctx.config.tmux);
         debug!("cli_after_config_init: {self:#?}");
 
         match self.ctx.config.general.decoration {
@@ -145,47 +167,528 @@ pub enum Commands {
         #[arg(short, long, value_enum, default_value_t=OutputFormat::Json)]
         output: OutputFormat,
     },
+
+    /// Open a new numbered throwaway session.
+    Scratch {
+        /// Overrides 'scratch.prefix' from the config file.
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Overrides 'scratch.path' from the config file.
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Overrides 'scratch.layout' from the config file.
+        #[arg(long)]
+        layout: Option<String>,
+
+        /// Kill the session once its last client detaches.
+        #[arg(long)]
+        ephemeral: bool,
+    },
+
+    /// Pick any pane on the server and switch straight to it.
+    Jump,
+
+    /// Go back to the previously visited session.
+    Last,
+
+    /// Search the shell history of a project with 'isolated_history' set, or of all of them, and
+    /// print the picked command.
+    History {
+        /// Name of the project in the config file.
+        project: Option<String>,
+    },
+
+    /// Toggle a popup with the current project's scratchpad, a hidden session that keeps its
+    /// shell and history between popups.
+    Popup,
+
+    /// Run a project task from the config file or the project's '.axl.yml' in a window named
+    /// after it, and report whether it succeeded.
+    Run {
+        /// Opens a picker when not provided.
+        task: Option<String>,
+
+        /// Defaults to the current session, or outside tmux the session started in the current
+        /// directory.
+        #[arg(short, long)]
+        session: Option<SessionName>,
+
+        /// Start the task without waiting for it to finish.
+        #[arg(short, long)]
+        detach: bool,
+    },
+
+    /// Type a command into a pane of each matching session, like 'git pull' in every project.
+    Exec {
+        /// Session names or globs ('*' and '?'), opens a picker when none are provided.
+        #[arg(short, long, num_args = 1..)]
+        sessions: Vec<String>,
+
+        /// Window, or 'window.pane', to run in, defaults to the active pane.
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Wait for the command to finish and report its exit status, the panes need a POSIX
+        /// shell prompt.
+        #[arg(short, long)]
+        wait: bool,
+
+        /// Seconds to wait for before giving up.
+        #[arg(long, default_value_t = 300, requires = "wait")]
+        timeout: u64,
+
+        /// Command to type, a single argument is typed as is ('git pull && make'), several are
+        /// quoted as needed.
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Run a session on its own socket so other users on this machine can attach to it.
+    Share {
+        /// Session to share, started on the shared server from its path when it is not there yet.
+        session: SessionName,
+
+        /// Users allowed to attach, tmux 3.3 and newer only let the owner attach otherwise.
+        #[arg(short, long, num_args = 1..)]
+        users: Vec<String>,
+
+        /// Group given access to the socket, defaults to the socket's current group.
+        #[arg(short, long)]
+        group: Option<String>,
+
+        /// Let the users type into the session instead of only watching.
+        #[arg(short, long, conflicts_with = "revoke")]
+        write: bool,
+
+        /// Take access away from the users, or from everyone when none are given.
+        #[arg(long)]
+        revoke: bool,
+    },
+
+    /// Notify through 'watch.notify_command', or a tmux message, when a session or window goes
+    /// quiet or prints a line matching a pattern.
+    #[command(group(ArgGroup::new("alerts").required(true).multiple(true)))]
+    Watch {
+        /// Session to watch, or a single window as '<session>:<window>'.
+        target: String,
+
+        /// Notify once there was no output for this long, like '30s' or '5m'.
+        #[arg(long, value_parser = parse_duration, group = "alerts")]
+        silence: Option<Duration>,
+
+        /// Notify when a new line of output matches this regex, like 'error|FAILED'.
+        #[arg(long, value_parser = Regex::new, group = "alerts")]
+        pattern: Option<Regex>,
+
+        /// Stop watching.
+        #[arg(long, group = "alerts", conflicts_with_all = ["silence", "pattern"])]
+        stop: bool,
+
+        /// Run by the tmux hooks for the window that raised the alert.
+        #[arg(long, hide = true, group = "alerts", conflicts_with_all = ["silence", "pattern", "stop"])]
+        alert: Option<WatchAlert>,
+    },
+
+    /// Record the output of every pane in a session to files.
+    Record {
+        #[command(subcommand)]
+        command: RecordCommands,
+    },
+
+    /// Manage tmux sessions.
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// Print a status-right segment with the project, git branch and state, and sessions with
+    /// alerts, cached for 'status.cache_seconds'.
+    ///
+    /// Meant for tmux.conf: set -g status-right "#(axl --decoration none status --session
+    /// '#{session_name}' --path '#{pane_current_path}')"
+    Status {
+        /// Session to describe as tmux prints it in '#{session_name}', defaults to the current one.
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Directory to read git state from, defaults to the current pane's.
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Integrate with tmux itself.
+    Tmux {
+        #[command(subcommand)]
+        command: TmuxCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, Display)]
+pub enum TmuxCommands {
+    /// Print tmux.conf bindings that open each command in a popup.
+    Init {
+        /// Report whether the running server has the bindings loaded instead.
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+impl TmuxCommands {
+    #[instrument(skip(command, context), err)]
+    fn handle(command: &Self, context: &AxlContext) -> Result<()> {
+        match command {
+            Self::Init { check: false } => {
+                print!("{}", TmuxCmd::bindings_conf(&context.config.tmux)?);
+            }
+            Self::Init { check: true } => {
+                // Missing features have a fallback, but it is worth knowing why things differ.
+                for feature in TmuxFeature::iter() {
+                    if let Err(err) = TmuxCmd::require(feature) {
+                        println!("{}", err.to_string().yellow());
+                    }
+                }
+                if FzfCmd::popup_args().is_empty() {
+                    let msg = format!(
+                        "fzf pickers open inline, popups need fzf {FZF_TMUX_VERSION} and tmux {}",
+                        TmuxFeature::Popup.min_version()
+                    );
+                    println!("{}", msg.yellow());
+                }
+                for status in TmuxCmd::check_bindings(&context.config.tmux)? {
+                    let binding = &status.binding;
+                    let state = match (&status.current, status.loaded) {
+                        (_, true) => "loaded".green(),
+                        (None, false) => "missing".yellow(),
+                        (Some(current), false) => format!("bound to: {current}").red(),
+                    };
+                    println!("{:<8} {:<14} {state}", binding.key, binding.name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Subcommand, Debug, Display)]
+pub enum RecordCommands {
+    /// Start recording each pane of a session to '$XDG_STATE_HOME/axl/recordings/<session>/'.
+    Start {
+        /// Defaults to the current session.
+        session: Option<SessionName>,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        format: RecordFormat,
+    },
+
+    /// Stop recording the panes of a session.
+    Stop {
+        /// Defaults to the current session.
+        session: Option<SessionName>,
+    },
+
+    /// List recordings, with the ones still being written marked active.
+    List {
+        #[arg(short, long, value_enum, default_value_t=OutputFormat::Json)]
+        output: OutputFormat,
+    },
+
+    /// Append standard input to a recording, run by 'pipe-pane' for each recorded pane.
+    #[command(hide = true)]
+    Sink {
+        output: PathBuf,
+
+        #[arg(long, value_enum, default_value_t)]
+        format: RecordFormat,
+
+        #[arg(long, default_value_t = 80)]
+        width: u32,
+
+        #[arg(long, default_value_t = 24)]
+        height: u32,
+
+        #[arg(long, default_value = "")]
+        title: String,
+    },
+}
+
+impl RecordCommands {
+    #[instrument(skip(command), err)]
+    fn handle(command: &Self) -> Result<()> {
+        let session_or_current = |session: &Option<SessionName>| {
+            session.clone().unwrap_or_else(TmuxCmd::get_current_session)
+        };
+        match command {
+            Self::Start { session, format } => {
+                let session = session_or_current(session);
+                let started = TmuxCmd::start_recording(&session, *format)?;
+                println!("Recording {} panes of {session}", started.len());
+                started
+                    .iter()
+                    .for_each(|path| println!("  {}", path.to_string_lossy()));
+            }
+            Self::Stop { session } => {
+                let session = session_or_current(session);
+                let stopped = TmuxCmd::stop_recording(&session)?;
+                println!("Stopped recording {stopped} panes of {session}");
+            }
+            Self::List { output } => {
+                formatted_print(output, recordings(&TmuxCmd::active_recordings()?)?)?;
+            }
+            Self::Sink {
+                output,
+                format,
+                width,
+                height,
+                title,
+            } => {
+                record_sink(
+                    &mut std::io::stdin().lock(),
+                    output,
+                    *format,
+                    (*width, *height),
+                    title,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Subcommand, Debug, Display)]
+pub enum SessionCommands {
+    /// Save every session to '$XDG_STATE_HOME/axl/sessions.json', or a file named after the
+    /// socket for other servers.
+    Save {
+        /// Do not print where the snapshot was written.
+        #[arg(short, long)]
+        silent: bool,
+    },
+
+    /// Recreate saved sessions that are not already running.
+    Restore {
+        /// Do not re-run the commands that were in the foreground of each pane.
+        #[arg(long)]
+        no_commands: bool,
+    },
+
+    /// Save a snapshot from tmux hooks whenever sessions change.
+    Autosave {
+        /// Remove the hooks instead of installing them.
+        #[arg(long)]
+        disable: bool,
+    },
+
+    /// Record switches made outside of axl in the session history through a tmux hook.
+    Track {
+        /// Remove the hook instead of installing it.
+        #[arg(long)]
+        disable: bool,
+    },
+
+    /// Record a visit to a session, used by the 'session track' hook.
+    #[command(hide = true)]
+    Visited { name: String },
+
+    /// List sessions with their ids, clients, windows, times and paths.
+    List {
+        #[arg(short, long, value_enum, default_value_t=OutputFormat::Json)]
+        output: OutputFormat,
+
+        /// Include every server from the config ('tmux.servers' and project sockets).
+        #[arg(short, long)]
+        all_servers: bool,
+    },
+
+    /// Kill sessions that are idle, whose path was deleted, or that have no clients, except for
+    /// the current one and those in 'tmux.pinned'.
+    #[command(group(ArgGroup::new("criteria").required(true).multiple(true)))]
+    Prune {
+        /// Sessions without activity for longer than this, like '2w', '3d' or '12h'.
+        #[arg(long, value_parser = parse_duration, group = "criteria")]
+        idle: Option<Duration>,
+
+        /// Sessions whose path no longer exists.
+        #[arg(long, group = "criteria")]
+        missing_path: bool,
+
+        /// Sessions without attached clients.
+        #[arg(long, group = "criteria")]
+        detached: bool,
+
+        /// Only list the sessions that would be killed.
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Kill without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List dead panes and panes whose directory was deleted, and offer to respawn them with
+    /// the command they were started with.
+    Health {
+        #[arg(short, long, value_enum, default_value_t=OutputFormat::Json)]
+        output: OutputFormat,
+
+        /// Only list the panes.
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Respawn every listed pane without asking.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Kill sessions picked interactively, or every session matching one of the patterns.
+    Kill {
+        /// Session names or globs ('*' and '?'), opens a picker when none are provided.
+        patterns: Vec<String>,
+    },
+}
+
+impl SessionCommands {
+    #[instrument(skip(command, context), err)]
+    fn handle(command: &Self, context: &AxlContext) -> Result<()> {
+        match command {
+            Self::Save { silent } => {
+                let (path, snapshot) = TmuxCmd::save_snapshot()?;
+                if !silent {
+                    println!(
+                        "Saved {} sessions to {}",
+                        snapshot.sessions.len(),
+                        path.to_string_lossy()
+                    );
+                }
+            }
+            Self::Restore { no_commands } => {
+                let restored = TmuxCmd::restore_snapshot(!no_commands, &context.config)?;
+                println!("Restored {} sessions", restored.len());
+                restored.iter().for_each(|name| println!("  {name}"));
+            }
+            Self::Autosave { disable } => {
+                TmuxCmd::set_autosave(!disable)?;
+            }
+            Self::Track { disable } => {
+                TmuxCmd::set_history_tracking(!disable)?;
+            }
+            Self::Visited { name } => {
+                SessionHistory::record(&SessionName::from_encoded(name));
+            }
+            Self::List {
+                output,
+                all_servers,
+            } => {
+                let sessions = if *all_servers {
+                    TmuxCmd::sessions_on(&context.config.tmux_servers())?
+                } else {
+                    TmuxCmd::sessions()?
+                };
+                formatted_print(output, sessions)?;
+            }
+            Self::Prune {
+                idle,
+                missing_path,
+                detached,
+                dry_run,
+                yes,
+            } => {
+                let criteria = PruneCriteria {
+                    idle: *idle,
+                    missing_path: *missing_path,
+                    detached: *detached,
+                };
+                // Outside of tmux there is no session to keep, the one tmux would call current
+                // is only the most recently used.
+                let current = TmuxCmd::in_session().then(TmuxCmd::get_current_session);
+                let candidates = criteria.candidates(
+                    TmuxCmd::sessions()?,
+                    current.as_ref(),
+                    &context.config.tmux.pinned,
+                    SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                );
+                if candidates.is_empty() {
+                    println!("No sessions to prune");
+                    return Ok(());
+                }
+
+                candidates.iter().for_each(|c| println!("{c}"));
+                if *dry_run {
+                    return Ok(());
+                }
+                let confirmed = *yes
+                    || Confirm::new(&format!("Kill {} sessions?", candidates.len()))
+                        .with_default(false)
+                        .prompt()?;
+                if confirmed {
+                    for candidate in candidates {
+                        if TmuxCmd::kill_with_hooks(&candidate.session.name, &context.config)? {
+                            println!("Killed {}", candidate.session.name);
+                        } else {
+                            eprintln!("Could not kill {}", candidate.session.name);
+                        }
+                    }
+                }
+            }
+            Self::Health {
+                output,
+                dry_run,
+                yes,
+            } => {
+                let unhealthy = TmuxCmd::unhealthy_panes()?;
+                formatted_print(output, &unhealthy)?;
+                if *dry_run {
+                    return Ok(());
+                }
+                for pane in &unhealthy {
+                    let confirmed = *yes
+                        || Confirm::new(&format!("Respawn {pane}?"))
+                            .with_default(false)
+                            .prompt()?;
+                    if confirmed {
+                        TmuxCmd::respawn_pane(pane)?;
+                        println!("Respawned {}", pane.id);
+                    }
+                }
+            }
+            Self::Kill { patterns } => {
+                let multiplexer = context.config.general.multiplexer;
+                let to_kill = select_sessions(patterns, multiplexer.list_sessions()?)?;
+
+                let killed = multiplexer.kill_sessions(
+                    &to_kill,
+                    &multiplexer.get_current_session(),
+                    &context.config,
+                )?;
+                killed.iter().for_each(|s| println!("Killed {s}"));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Commands {
-    #[instrument(skip(command, _context, _args), err)]
-    fn handle(command: &Self, _context: &AxlContext, _args: &SharedArgs) -> Result<()> {
+    #[instrument(skip(command, context, _args), err)]
+    fn handle(command: &Self, context: &AxlContext, _args: &SharedArgs) -> Result<()> {
         match command {
             Self::ProjectMenu => {
-                trace!("picking from existing sessions...");
-                let sessions = TmuxCmd::list_sessions()?;
+                let multiplexer = context.config.general.multiplexer;
+                trace!("picking from existing {multiplexer} sessions...");
+                let mut sessions = multiplexer.list_sessions()?;
+                SessionHistory::load()?.sort(&mut sessions, &multiplexer.get_current_session());
                 let picked_session = &FzfCmd::find_vec(sessions.clone())?;
                 if picked_session.trim().is_empty() {
                     return Ok(());
                 }
 
-                if sessions.contains(
-                    &picked_session
-                        .replace(".", "_")
-                        .replace("$", "")
-                        .replace("^", "")
-                        .replace("'", "")
-                        .replace("!", ""),
-                ) {
-                    TmuxCmd::open_existing(
-                        &picked_session
-                            .replace(".", "_")
-                            .replace("$", "")
-                            .replace("^", "")
-                            .replace("'", "")
-                            .replace("!", ""),
-                    )
+                if let Some(existing) = sessions.iter().find(|s| s.to_string() == *picked_session) {
+                    multiplexer.open_existing(existing, &context.config)
                 } else {
                     let zoxide_path = ZoxideCmd::query_interactive(picked_session)?;
-                    TmuxCmd::open(
-                        &zoxide_path,
-                        &picked_session
-                            .replace(".", "_")
-                            .replace("$", "")
-                            .replace("^", "")
-                            .replace("'", "")
-                            .replace("!", ""),
-                    )
+                    let name =
+                        SessionName::for_path(&zoxide_path, &multiplexer.list_session_paths()?);
+                    multiplexer.open(&zoxide_path, &name, &context.config)
                 }
             }
             Self::Info { output } => {
@@ -197,10 +700,242 @@ impl Commands {
                 formatted_print(output, info)?;
                 Ok(())
             }
+            Self::Scratch {
+                prefix,
+                path,
+                layout,
+                ephemeral,
+            } => {
+                let scratch = &context.config.scratch;
+                let layout = layout
+                    .as_ref()
+                    .or(scratch.layout.as_ref())
+                    .map(|l| context.config.named_layout(l))
+                    .transpose()?;
+                TmuxCmd::unique_session(
+                    prefix.as_ref().unwrap_or(&scratch.prefix),
+                    path.as_ref()
+                        .or(scratch.path.as_ref())
+                        .map(PathBuf::as_path),
+                    layout,
+                    *ephemeral,
+                    &context.config,
+                )?;
+                Ok(())
+            }
+            Self::Jump => {
+                let panes = TmuxCmd::panes()?;
+                if panes.is_empty() {
+                    Err(Error::NoSessionsFound)?;
+                }
+                let picked = FzfCmd::find_vec(panes.clone())?;
+                panes
+                    .iter()
+                    .find(|pane| pane.to_string() == picked)
+                    .map_or(Ok(()), |pane| TmuxCmd::jump_to_pane(pane, &context.config))
+            }
+            Self::Last => {
+                let multiplexer = context.config.general.multiplexer;
+                let previous = SessionHistory::load()?
+                    .previous(
+                        &multiplexer.get_current_session(),
+                        &multiplexer.list_sessions()?,
+                    )
+                    .cloned()
+                    .ok_or(Error::NoPreviousSession)?;
+                multiplexer.open_existing(&previous, &context.config)
+            }
+            Self::History { project } => {
+                let picked = FzfCmd::find_vec(history_entries(project.as_deref())?)?;
+                if !picked.trim().is_empty() {
+                    println!("{picked}");
+                }
+                Ok(())
+            }
+            Self::Popup => TmuxCmd::toggle_popup(&context.config),
+            Self::Run {
+                task,
+                session,
+                detach,
+            } => {
+                let session = TmuxCmd::task_session(session.as_ref())?;
+                let tasks = TmuxCmd::session_tasks(&session, &context.config)?;
+                if tasks.is_empty() {
+                    Err(Error::NoTasksFound(session.to_string()))?;
+                }
+                let task = match task {
+                    Some(task) => task.clone(),
+                    None => FzfCmd::find_vec(tasks.keys().cloned().collect())?,
+                };
+                if task.trim().is_empty() {
+                    return Ok(());
+                }
+                let command = tasks
+                    .get(&task)
+                    .ok_or_else(|| Error::TaskNotFound(task.clone(), session.to_string()))?;
+
+                println!("Running {task} in {session}");
+                match TmuxCmd::run_task(&session, &task, command, !detach)? {
+                    Some(0) => println!("{}", format!("{task} succeeded").green()),
+                    Some(status) => Err(Error::TaskFailed(task, status))?,
+                    None => {}
+                }
+                Ok(())
+            }
+            Self::Status { session, path } => {
+                let session = session
+                    .as_deref()
+                    .map_or_else(TmuxCmd::get_current_session, SessionName::from_encoded);
+                let path = path.clone().map_or_else(TmuxCmd::current_path, Ok)?;
+                let status = &context.config.status;
+
+                let segment = cached_segment(
+                    &format!("{}\n{}", session.as_str(), path.to_string_lossy()),
+                    status.cache_seconds,
+                    || {
+                        Ok(StatusSegment {
+                            project: context
+                                .config
+                                .project(&session.to_string(), &path)
+                                .map_or_else(|| session.to_string(), |p| p.name.clone()),
+                            git: GitStatus::read(&path),
+                            activity: TmuxCmd::sessions_with_alerts(&session)?,
+                        }
+                        .render(&status.template))
+                    },
+                )?;
+                println!("{segment}");
+                Ok(())
+            }
+            Self::Exec {
+                sessions,
+                target,
+                wait,
+                timeout,
+                command,
+            } => {
+                let sessions = select_sessions(sessions, TmuxCmd::list_sessions()?)?;
+                let results = TmuxCmd::exec(
+                    &sessions,
+                    target.as_deref(),
+                    &command_line(command),
+                    wait.then(|| Duration::from_secs(*timeout)),
+                )?;
+
+                let width = sessions.iter().map(|s| s.to_string().len()).max();
+                for result in &results {
+                    let status = if result.status.is_success() {
+                        result.status.to_string().green()
+                    } else {
+                        result.status.to_string().red()
+                    };
+                    println!(
+                        "{:<width$} {:<6} {status}",
+                        result.session.to_string(),
+                        result.pane,
+                        width = width.unwrap_or_default(),
+                    );
+                }
+
+                let failed = results.iter().filter(|r| !r.status.is_success()).count();
+                if failed > 0 {
+                    Err(Error::ExecFailed(failed))?;
+                }
+                Ok(())
+            }
+            Self::Share {
+                session,
+                users,
+                group,
+                write,
+                revoke: false,
+            } => {
+                let shared =
+                    TmuxCmd::share(session, users, group.as_deref(), *write, &context.config)?;
+                println!("Sharing {} on {}", shared.name, shared.socket);
+                if TmuxCmd::supports(TmuxFeature::ServerAccess) {
+                    println!("  read-only:  {}", shared.attach_read_only);
+                    println!("  read-write: {}", shared.attach_read_write);
+                    if users.is_empty() {
+                        let msg = "only you can attach until users are added with --users";
+                        println!("{}", msg.yellow());
+                    }
+                } else {
+                    // Without server-access, attaching read-only is up to whoever attaches.
+                    println!("  attach: {}", shared.attach_read_write);
+                    let msg = format!(
+                        "anyone in the socket's group can attach read-write, limiting access needs tmux {}",
+                        TmuxFeature::ServerAccess.min_version()
+                    );
+                    println!("{}", msg.yellow());
+                }
+                Ok(())
+            }
+            Self::Share {
+                session,
+                users,
+                revoke: true,
+                ..
+            } => {
+                let revoked = TmuxCmd::revoke_share(session, users)?;
+                if users.is_empty() {
+                    println!("Closed {session} to everyone else");
+                }
+                revoked.iter().for_each(|u| println!("Revoked {u}"));
+                Ok(())
+            }
+            Self::Watch {
+                target,
+                alert: Some(alert),
+                ..
+            } => TmuxCmd::watch_alert(target, *alert, &context.config),
+            Self::Watch {
+                target, stop: true, ..
+            } => {
+                let target = WatchTarget::parse(target);
+                let windows = TmuxCmd::unwatch(&target)?;
+                println!("Stopped watching {} windows of {target}", windows.len());
+                Ok(())
+            }
+            Self::Watch {
+                target,
+                silence,
+                pattern,
+                ..
+            } => {
+                let target = WatchTarget::parse(target);
+                let windows = TmuxCmd::watch(&target, *silence, pattern.as_ref())?;
+                println!("Watching {} windows of {target}", windows.len());
+                Ok(())
+            }
+            Self::Record { command } => RecordCommands::handle(command),
+            Self::Session { command } => SessionCommands::handle(command, context),
+            Self::Tmux { command } => TmuxCommands::handle(command, context),
         }
     }
 }
 
+/// Sessions picked interactively, or every session matching one of the patterns.
+fn select_sessions(patterns: &[String], sessions: Vec<SessionName>) -> Result<Vec<SessionName>> {
+    let selected: Vec<_> = if patterns.is_empty() {
+        let picked = FzfCmd::find_vec_multi(sessions.clone())?;
+        sessions
+            .into_iter()
+            .filter(|s| picked.contains(&s.to_string()))
+            .collect()
+    } else {
+        sessions
+            .into_iter()
+            .filter(|s| patterns.iter().any(|p| glob_match(p, &s.to_string())))
+            .collect()
+    };
+
+    if selected.is_empty() {
+        Err(Error::NoSessionsFound)?
+    }
+    Ok(selected)
+}
+
 #[derive(Args, Debug)]
 pub struct SharedArgs {
     #[clap(flatten)]
@@ -210,13 +945,17 @@ pub struct SharedArgs {
     #[arg(short, long, env("AXL_CONFIG_PATH"))]
     config_path: Option<PathBuf>,
 
-    /// Helpful for tmux popup prompts to see why a command failed.
-    #[arg(long, value_enum, default_value_t)]
-    pub on_error: OnError,
+    /// Helpful for tmux popup prompts to see why a command failed, overrides 'general.on_error'.
+    #[arg(long, value_enum)]
+    pub on_error: Option<OnError>,
 
     /// Control which decorations are displayed.
     #[arg(long, value_enum, default_value_t)]
     pub decoration: DecorationOption,
+
+    /// tmux server to use, a socket name ('-L') or a socket path ('-S').
+    #[arg(short = 'L', long, env("AXL_TMUX_SOCKET"))]
+    pub socket: Option<String>,
 }
 
 #[derive(Debug)]
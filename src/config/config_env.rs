@@ -1,15 +1,17 @@
 use std::{env, path::PathBuf};
 
+use clap::ValueEnum;
 use thiserror::Error;
 use tracing::trace;
 
 use super::{
     config_file::DecorationOption,
     constants::{
-        DEFAULT_DECORATIONS_KEY, HOME_DIR_KEY, XDG_CONFIG_HOME_DIR_KEY, XDG_DATA_HOME_DIR_KEY,
-        XDG_STATE_HOME_DIR_KEY,
+        DEFAULT_DECORATIONS_KEY, DEFAULT_MULTIPLEXER_KEY, HOME_DIR_KEY, XDG_CONFIG_HOME_DIR_KEY,
+        XDG_DATA_HOME_DIR_KEY, XDG_STATE_HOME_DIR_KEY,
     },
 };
+use crate::multiplexer::Multiplexers;
 
 pub enum ConfigEnvKey {
     Home,
@@ -17,6 +19,7 @@ pub enum ConfigEnvKey {
     XDGDataHome,
     XDGStateHome,
     Decorations,
+    DefaultMultiplexer,
 }
 
 impl ConfigEnvKey {
@@ -27,6 +30,7 @@ impl ConfigEnvKey {
             Self::XDGDataHome => XDG_DATA_HOME_DIR_KEY,
             Self::XDGStateHome => XDG_STATE_HOME_DIR_KEY,
             Self::Decorations => DEFAULT_DECORATIONS_KEY,
+            Self::DefaultMultiplexer => DEFAULT_MULTIPLEXER_KEY,
         }
     }
 
@@ -37,6 +41,7 @@ impl ConfigEnvKey {
             Self::XDGDataHome => "",
             Self::XDGStateHome => "",
             Self::Decorations => "",
+            Self::DefaultMultiplexer => "",
         }
     }
 }
@@ -64,14 +69,34 @@ impl TryFrom<ConfigEnvKey> for PathBuf {
                     Ok(home)
                 }
             },
-            ConfigEnvKey::XDGDataHome => Ok(Self::from(
-                env::var(ConfigEnvKey::XDGDataHome.as_str())
-                    .expect("XDG_DATA_HOME env var should be set"),
-            )),
-            ConfigEnvKey::XDGStateHome => Ok(Self::from(
-                env::var(ConfigEnvKey::XDGStateHome.as_str())
-                    .expect("XDG_STATE_HOME env var should be set"),
-            )),
+            ConfigEnvKey::XDGDataHome => match env::var(ConfigEnvKey::XDGDataHome.as_str()) {
+                Ok(data_dir) => Ok(Self::from(data_dir)),
+                Err(_err) => {
+                    let mut home = Self::try_from(ConfigEnvKey::Home)?;
+                    home.push(".local");
+                    home.push("share");
+                    trace!(
+                        "Error: error reading ${}. Using [{}]",
+                        ConfigEnvKey::XDGDataHome.as_str(),
+                        home.as_os_str().to_string_lossy()
+                    );
+                    Ok(home)
+                }
+            },
+            ConfigEnvKey::XDGStateHome => match env::var(ConfigEnvKey::XDGStateHome.as_str()) {
+                Ok(state_dir) => Ok(Self::from(state_dir)),
+                Err(_err) => {
+                    let mut home = Self::try_from(ConfigEnvKey::Home)?;
+                    home.push(".local");
+                    home.push("state");
+                    trace!(
+                        "Error: error reading ${}. Using [{}]",
+                        ConfigEnvKey::XDGStateHome.as_str(),
+                        home.as_os_str().to_string_lossy()
+                    );
+                    Ok(home)
+                }
+            },
             #[allow(unreachable_patterns)]
             // This is allowed because not all enum variants are guaranteed to be this type in the
             // futrue.
@@ -103,10 +128,27 @@ impl TryFrom<ConfigEnvKey> for DecorationOption {
     }
 }
 
+impl TryFrom<ConfigEnvKey> for Multiplexers {
+    type Error = ConfigError;
+    fn try_from(env_key: ConfigEnvKey) -> Result<Self, ConfigError> {
+        match env_key {
+            ConfigEnvKey::DefaultMultiplexer => {
+                let key = ConfigEnvKey::DefaultMultiplexer.as_str();
+                let value = env::var(key).map_err(|_| ConfigError::NotFound(key.to_string()))?;
+                Self::from_str(&value, true)
+                    .map_err(|_| ConfigError::InvalidValue(key.to_string(), value))
+            }
+            _ => panic!("this key cannot be converted to Multiplexers. {DEFAULT_PANIC_MSG}"),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("generic")]
     Generic,
     #[error("env variable [{0}] not found")]
     NotFound(String),
+    #[error("env variable [{0}] has invalid value [{1}]")]
+    InvalidValue(String, String),
 }
@@ -1,14 +1,25 @@
 use anyhow::Result;
 use clap::ValueEnum;
+use inquire::Text;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 use strum::Display;
 use tracing::{debug, instrument};
 
-use crate::config::config_env::ConfigEnvKey;
+use crate::{
+    config::{
+        config_env::{ConfigEnvKey, ConfigError},
+        layout::SessionLayout,
+    },
+    error::Error,
+    multiplexer::Multiplexers,
+    tmux::TmuxSocket,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct AxlContext {
@@ -22,11 +33,206 @@ pub struct AxlContext {
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
 pub struct AxlConfig {
     pub general: GeneralConfig,
+    #[serde(default)]
+    pub layouts: BTreeMap<String, SessionLayout>,
+    #[serde(default)]
+    pub projects: Vec<ProjectConfig>,
+    #[serde(default)]
+    pub scratch: ScratchConfig,
+    #[serde(default)]
+    pub tmux: TmuxConfig,
+    /// Run for every session, before the project's own hooks.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub status: StatusConfig,
+    #[serde(default)]
+    pub watch: WatchConfig,
+}
+
+/// Settings for `axl status`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusConfig {
+    /// `{project}`, `{branch}`, `{dirty}`, `{ahead}`, `{behind}` and `{activity}` are replaced
+    /// with their values, the counts come with a leading space and symbol and are empty when
+    /// zero. Colors are switched with `{hot-pink}`, `{tiffany-blue}`, `{mint}`, `{yellow}` and
+    /// `{reset}`.
+    #[serde(default = "StatusConfig::default_template")]
+    pub template: String,
+    /// How long a rendered segment is reused before git and tmux are asked again.
+    #[serde(default = "StatusConfig::default_cache_seconds")]
+    pub cache_seconds: u64,
+}
+
+impl StatusConfig {
+    #[allow(clippy::literal_string_with_formatting_args)] // Template placeholders, not format args.
+    fn default_template() -> String {
+        "{hot-pink}{project} {tiffany-blue}{branch}{yellow}{dirty}{mint}{ahead}{behind}{hot-pink}{activity}{reset}".to_string()
+    }
+
+    const fn default_cache_seconds() -> u64 {
+        10
+    }
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            template: Self::default_template(),
+            cache_seconds: Self::default_cache_seconds(),
+        }
+    }
+}
+
+/// Settings for `axl watch`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct WatchConfig {
+    /// Run for each notification instead of showing it with `display-message`, with
+    /// `AXL_SESSION_NAME`, `AXL_WATCH_WINDOW` and `AXL_WATCH_MESSAGE` set, like
+    /// `notify-send axl "$AXL_WATCH_MESSAGE"`.
+    pub notify_command: Option<String>,
+}
+
+/// Shell commands run at points in a session's life, with `AXL_SESSION_NAME` and
+/// `AXL_SESSION_PATH` set and the session path as the working directory.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct HooksConfig {
+    /// After the session is created.
+    pub on_create: Option<String>,
+    /// Before a client attaches or switches to the session.
+    pub on_attach: Option<String>,
+    /// Before the session is killed.
+    pub on_kill: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct TmuxConfig {
+    /// Send commands over a single `tmux -C` connection instead of starting a tmux process for
+    /// each one. Falls back to separate processes when the connection can not be made.
+    ///
+    /// The connection is a client attached to the most recently used session, so it runs
+    /// `client-attached` hooks and makes that session look attached while axl runs.
+    #[serde(default)]
+    pub control_mode: bool,
+    /// Server used for every command, a socket name (`-L`) or a path (`-S`). Overridden by the
+    /// `--socket` flag.
+    #[serde(default)]
+    pub socket: Option<TmuxSocket>,
+    /// Extra servers included when listing sessions across servers.
+    #[serde(default)]
+    pub servers: Vec<TmuxSocket>,
+    /// Default size of the popups opened by `axl tmux init` bindings.
+    #[serde(default)]
+    pub popup: PopupConfig,
+    /// Overrides for `axl tmux init` bindings, keyed by command (like `project-menu`).
+    #[serde(default)]
+    pub bindings: BTreeMap<String, BindingConfig>,
+    /// Session names or globs that `axl session prune` never kills.
+    #[serde(default)]
+    pub pinned: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PopupConfig {
+    /// A number of cells or a percentage of the terminal, like `80%`.
+    #[serde(default = "PopupConfig::default_size")]
+    pub width: String,
+    #[serde(default = "PopupConfig::default_size")]
+    pub height: String,
+}
+
+impl PopupConfig {
+    fn default_size() -> String {
+        "80%".to_string()
+    }
+}
+
+impl Default for PopupConfig {
+    fn default() -> Self {
+        Self {
+            width: Self::default_size(),
+            height: Self::default_size(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct BindingConfig {
+    /// Key in the prefix table, an empty key leaves the command unbound.
+    pub key: Option<String>,
+    pub width: Option<String>,
+    pub height: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
 pub struct GeneralConfig {
     pub decoration: DecorationOption,
+    /// Overridden by `AXL_DEFAULT_MULTIPLEXER` when it is set.
+    #[serde(default)]
+    pub multiplexer: Multiplexers,
+    /// Layout used for new sessions when the project does not pick one.
+    #[serde(default)]
+    pub default_layout: Option<String>,
+    /// Overridden by the `--on-error` flag.
+    #[serde(default)]
+    pub on_error: OnError,
+}
+
+/// Defaults for `axl scratch` sessions, each can be overridden with a flag.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScratchConfig {
+    /// Scratch sessions are named `<prefix><n>`.
+    #[serde(default = "ScratchConfig::default_prefix")]
+    pub prefix: String,
+    /// Defaults to `$HOME`.
+    pub path: Option<PathBuf>,
+    /// Name of an entry in `layouts`.
+    pub layout: Option<String>,
+}
+
+impl ScratchConfig {
+    fn default_prefix() -> String {
+        "scratch-".to_string()
+    }
+}
+
+impl Default for ScratchConfig {
+    fn default() -> Self {
+        Self {
+            prefix: Self::default_prefix(),
+            path: None,
+            layout: None,
+        }
+    }
+}
+
+/// Settings for a single project, matched by path first and then by session name.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ProjectConfig {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    /// Name of an entry in `layouts`.
+    pub layout: Option<String>,
+    /// Server the project's session is opened on instead of `tmux.socket`.
+    #[serde(default)]
+    pub socket: Option<TmuxSocket>,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Set in every pane of the session, `${name}` and `${path}` are replaced with the project's
+    /// name and path.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Also set the variables from a `.env` file in the project root.
+    #[serde(default)]
+    pub dotenv: bool,
+    /// Point `HISTFILE` at a file of the project's own under `$XDG_DATA_HOME/axl/history/`, so
+    /// bash and zsh in its sessions keep a separate history.
+    #[serde(default)]
+    pub isolated_history: bool,
+    /// Shell commands run by `axl run <task>`, keyed by task name. A `.axl.yml` in the project
+    /// root can add its own under `tasks`.
+    #[serde(default)]
+    pub tasks: BTreeMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Default, ValueEnum, Debug, Clone, PartialEq, Eq, Display)]
@@ -53,6 +259,25 @@ pub enum OnError {
     LongDelay,
 }
 
+impl OnError {
+    /// Give the user a chance to read an error that was just printed.
+    pub fn wait(&self) -> Result<()> {
+        match self {
+            Self::None => {}
+            Self::Pause => {
+                Text::new("Press ENTER to continue...").prompt()?;
+            }
+            Self::ShortDelay => {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Self::LongDelay => {
+                std::thread::sleep(Duration::from_millis(5000));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl AxlConfig {
     #[instrument(err)]
     pub fn from_file(config_path: &Path) -> Result<Self> {
@@ -67,9 +292,66 @@ impl AxlConfig {
             loaded_config.general.decoration = decoration_env;
         }
 
+        match Multiplexers::try_from(ConfigEnvKey::DefaultMultiplexer) {
+            Ok(multiplexer_env) => loaded_config.general.multiplexer = multiplexer_env,
+            Err(ConfigError::NotFound(_)) => {}
+            Err(err) => Err(err)?,
+        }
+
         debug!("config: {:#?}", loaded_config);
         Ok(loaded_config)
     }
+
+    pub fn project(&self, name: &str, path: &Path) -> Option<&ProjectConfig> {
+        self.projects
+            .iter()
+            .find(|p| p.path.as_deref().is_some_and(|p_path| p_path == path))
+            .or_else(|| self.projects.iter().find(|p| p.name == name))
+    }
+
+    #[instrument(skip(self), err)]
+    pub fn layout(&self, name: &str, path: &Path) -> Result<Option<&SessionLayout>> {
+        let layout_name = self
+            .project(name, path)
+            .and_then(|p| p.layout.as_ref())
+            .or(self.general.default_layout.as_ref());
+
+        layout_name.map(|l| self.named_layout(l)).transpose()
+    }
+
+    /// Environment for a new session, empty when it does not belong to a project.
+    #[instrument(skip(self), err)]
+    pub fn session_env(&self, name: &str, path: &Path) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .project(name, path)
+            .map(|p| p.session_env(path))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Every server known to the config: the configured one (`None` being the default server),
+    /// `tmux.servers` and project sockets, without duplicates.
+    pub fn tmux_servers(&self) -> Vec<Option<TmuxSocket>> {
+        let mut servers = vec![self.tmux.socket.clone()];
+        for socket in self
+            .tmux
+            .servers
+            .iter()
+            .chain(self.projects.iter().filter_map(|p| p.socket.as_ref()))
+        {
+            if !servers.contains(&Some(socket.clone())) {
+                servers.push(Some(socket.clone()));
+            }
+        }
+        servers
+    }
+
+    pub fn named_layout(&self, layout_name: &str) -> Result<&SessionLayout> {
+        Ok(self
+            .layouts
+            .get(layout_name)
+            .ok_or_else(|| Error::LayoutNotFound(layout_name.to_string()))?)
+    }
 }
 
 #[cfg(test)]
@@ -79,7 +361,16 @@ mod tests {
     use rstest::{fixture, rstest};
     use similar_asserts::assert_eq;
 
-    use crate::config::config_file::{DecorationOption, GeneralConfig};
+    use std::path::Path;
+
+    use crate::{
+        config::{
+            config_file::{DecorationOption, GeneralConfig, HooksConfig, OnError},
+            layout::{PaneLayout, SplitDirection},
+        },
+        multiplexer::Multiplexers,
+        tmux::TmuxSocket,
+    };
 
     use super::AxlConfig;
 
@@ -96,6 +387,98 @@ mod tests {
         file
     }
 
+    #[fixture]
+    fn config_file_zellij() -> NamedTempFile {
+        // Arrange
+        let file = NamedTempFile::new("config_file_test_zellij.txt")
+            .expect("test fixture tmp file can be created");
+        file.write_str(
+            "general:
+    decoration: none
+    multiplexer: zellij",
+        )
+        .expect("test fixture tmp file can be written to");
+        file
+    }
+
+    #[fixture]
+    fn config_file_layouts() -> NamedTempFile {
+        // Arrange
+        let file = NamedTempFile::new("config_file_test_layouts.txt")
+            .expect("test fixture tmp file can be created");
+        file.write_str(
+            "general:
+    decoration: none
+    default_layout: single
+layouts:
+    single:
+        windows:
+            - name: shell
+    dev:
+        windows:
+            - name: editor
+              panes:
+                  - command: nvim
+            - name: server
+              path: web
+              layout: even-horizontal
+              panes:
+                  - command: npm run dev
+                  - split: horizontal
+                    size: 30%
+projects:
+    - name: api
+      path: /code/api
+      layout: dev
+    - name: docs",
+        )
+        .expect("test fixture tmp file can be written to");
+        file
+    }
+
+    #[fixture]
+    fn config_file_sockets() -> NamedTempFile {
+        // Arrange
+        let file = NamedTempFile::new("config_file_test_sockets.txt")
+            .expect("test fixture tmp file can be created");
+        file.write_str(
+            "general:
+    decoration: none
+tmux:
+    servers:
+        - work
+        - /tmp/shared.sock
+projects:
+    - name: api
+      socket: work
+    - name: infra
+      socket: infra",
+        )
+        .expect("test fixture tmp file can be written to");
+        file
+    }
+
+    #[fixture]
+    fn config_file_hooks() -> NamedTempFile {
+        // Arrange
+        let file = NamedTempFile::new("config_file_test_hooks.txt")
+            .expect("test fixture tmp file can be created");
+        file.write_str(
+            "general:
+    decoration: none
+    on_error: pause
+hooks:
+    on_attach: git fetch
+projects:
+    - name: api
+      hooks:
+          on_create: docker compose up -d
+          on_kill: docker compose down",
+        )
+        .expect("test fixture tmp file can be written to");
+        file
+    }
+
     #[fixture]
     fn config_file_empty() -> NamedTempFile {
         // Arrange
@@ -116,8 +499,104 @@ mod tests {
             loaded_config,
             AxlConfig {
                 general: GeneralConfig {
-                    decoration: DecorationOption::VersionBanner
-                }
+                    decoration: DecorationOption::VersionBanner,
+                    multiplexer: Multiplexers::Tmux,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn should_read_multiplexer_from_file(
+        #[from(config_file_zellij)] config_file: NamedTempFile,
+    ) -> Result<()> {
+        let loaded_config = AxlConfig::from_file(config_file.path())?;
+
+        assert_eq!(
+            loaded_config,
+            AxlConfig {
+                general: GeneralConfig {
+                    decoration: DecorationOption::None,
+                    multiplexer: Multiplexers::Zellij,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn should_resolve_project_layout(
+        #[from(config_file_layouts)] config_file: NamedTempFile,
+    ) -> Result<()> {
+        let loaded_config = AxlConfig::from_file(config_file.path())?;
+
+        let dev = loaded_config
+            .layout("renamed", Path::new("/code/api"))?
+            .expect("project layout should be found by path");
+        assert_eq!(dev.windows.len(), 2);
+        assert_eq!(
+            dev.windows[1].panes[1],
+            PaneLayout {
+                split: SplitDirection::Horizontal,
+                size: Some("30%".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let single = loaded_config
+            .layout("docs", Path::new("/code/docs"))?
+            .expect("default layout should be used when project has none");
+        assert_eq!(single.windows[0].name.as_deref(), Some("shell"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn should_list_each_tmux_server_once(
+        #[from(config_file_sockets)] config_file: NamedTempFile,
+    ) -> Result<()> {
+        let loaded_config = AxlConfig::from_file(config_file.path())?;
+
+        assert_eq!(
+            loaded_config.tmux_servers(),
+            vec![
+                None,
+                Some(TmuxSocket("work".to_string())),
+                Some(TmuxSocket("/tmp/shared.sock".to_string())),
+                Some(TmuxSocket("infra".to_string())),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn should_read_global_and_project_hooks(
+        #[from(config_file_hooks)] config_file: NamedTempFile,
+    ) -> Result<()> {
+        let loaded_config = AxlConfig::from_file(config_file.path())?;
+
+        assert_eq!(loaded_config.general.on_error, OnError::Pause);
+        assert_eq!(
+            loaded_config.hooks,
+            HooksConfig {
+                on_attach: Some("git fetch".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            loaded_config.projects[0].hooks,
+            HooksConfig {
+                on_create: Some("docker compose up -d".to_string()),
+                on_attach: None,
+                on_kill: Some("docker compose down".to_string()),
             }
         );
 
@@ -134,8 +613,11 @@ mod tests {
             loaded_config,
             AxlConfig {
                 general: GeneralConfig {
-                    decoration: DecorationOption::All
-                }
+                    decoration: DecorationOption::All,
+                    multiplexer: Multiplexers::Tmux,
+                    ..Default::default()
+                },
+                ..Default::default()
             }
         );
 
@@ -2,13 +2,14 @@ use colored::{Colorize, CustomColor};
 use rand::Rng;
 use serde_derive::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter};
 
 pub const HOME_DIR_KEY: &str = "HOME";
 pub const XDG_CONFIG_HOME_DIR_KEY: &str = "XDG_CONFIG_HOME";
 pub const XDG_DATA_HOME_DIR_KEY: &str = "XDG_DATA_HOME";
 pub const XDG_STATE_HOME_DIR_KEY: &str = "XDG_STATE_HOME";
 pub const DEFAULT_DECORATIONS_KEY: &str = "AXL_DECORATIONS";
+pub const DEFAULT_MULTIPLEXER_KEY: &str = "AXL_DEFAULT_MULTIPLEXER";
 
 // version string constants
 pub const PROJ_NAME: &str = env!("CARGO_PKG_NAME");
@@ -60,7 +61,8 @@ pub struct CliInfo<'a> {
     pub os_platform: &'a str,
 }
 
-#[derive(EnumIter)]
+#[derive(EnumIter, Display, Debug, Clone, Copy)]
+#[strum(serialize_all = "kebab-case")]
 pub enum AxlColor {
     HotPink,
     TiffanyBlue,
@@ -68,6 +70,14 @@ pub enum AxlColor {
     Yellow,
 }
 
+impl AxlColor {
+    /// A tmux style that sets this color as the foreground, like `#[fg=#ffaebc]`.
+    pub fn tmux_style(self) -> String {
+        let CustomColor { r, g, b } = self.into();
+        format!("#[fg=#{r:02x}{g:02x}{b:02x}]")
+    }
+}
+
 impl From<AxlColor> for CustomColor {
     fn from(axl_color: AxlColor) -> Self {
         match axl_color {
@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// A named arrangement of windows and panes that is built when a session is created.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct SessionLayout {
+    #[serde(default)]
+    pub windows: Vec<WindowLayout>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct WindowLayout {
+    pub name: Option<String>,
+    /// Relative paths are resolved from the session path.
+    pub path: Option<PathBuf>,
+    /// Any layout accepted by `tmux select-layout`, applied after all panes are split.
+    pub layout: Option<String>,
+    /// The first pane is the one the window starts with, every other pane is split from the
+    /// pane before it.
+    #[serde(default)]
+    pub panes: Vec<PaneLayout>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct PaneLayout {
+    /// Relative paths are resolved from the window path.
+    pub path: Option<PathBuf>,
+    /// Typed into the pane's shell once it starts.
+    pub command: Option<String>,
+    #[serde(default)]
+    pub split: SplitDirection,
+    /// Passed to `split-window -l`, e.g. `20` lines/columns or `30%`.
+    pub size: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitDirection {
+    /// New pane is placed beside the previous one.
+    Horizontal,
+    /// New pane is placed below the previous one.
+    #[default]
+    Vertical,
+}
+
+impl SplitDirection {
+    pub const fn as_flag(&self) -> &'static str {
+        match self {
+            Self::Horizontal => "-h",
+            Self::Vertical => "-v",
+        }
+    }
+}
+
+/// Resolve an optional layout path against the directory it is relative to.
+pub fn resolve_path(base: &Path, path: Option<&PathBuf>) -> PathBuf {
+    path.map_or_else(|| base.to_path_buf(), |p| base.join(p))
+}
@@ -1,3 +1,6 @@
 pub mod config_env;
 pub mod config_file;
 pub mod constants;
+pub mod layout;
+pub mod session_env;
+pub mod tasks;
@@ -0,0 +1,127 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use tracing::{debug, instrument};
+
+use crate::{config::config_file::ProjectConfig, shell_history::histfile};
+
+const DOTENV_FILE: &str = ".env";
+const HISTFILE_KEY: &str = "HISTFILE";
+
+impl ProjectConfig {
+    /// Variables for a new session of this project rooted at `path`, `.env` entries and
+    /// `HISTFILE` first so the config file can override them.
+    #[instrument(skip(self), err)]
+    pub fn session_env(&self, path: &Path) -> Result<Vec<(String, String)>> {
+        let mut env = Vec::new();
+
+        if self.dotenv {
+            let dotenv_path = path.join(DOTENV_FILE);
+            match fs::read_to_string(&dotenv_path) {
+                Ok(contents) => env.extend(parse_dotenv(&contents)),
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    debug!("no {} in {}", DOTENV_FILE, path.to_string_lossy());
+                }
+                Err(err) => Err(err)?,
+            }
+        }
+
+        if self.isolated_history {
+            env.retain(|(k, _)| k != HISTFILE_KEY);
+            env.push((
+                HISTFILE_KEY.to_string(),
+                histfile(&self.name)?.to_string_lossy().to_string(),
+            ));
+        }
+
+        for (key, value) in &self.env {
+            env.retain(|(k, _)| k != key);
+            env.push((key.clone(), interpolate(value, &self.name, path)));
+        }
+
+        Ok(env)
+    }
+}
+
+/// Replace `${name}` and `${path}` with the project's name and path.
+#[allow(clippy::literal_string_with_formatting_args)] // Config placeholders, not format args.
+pub fn interpolate(value: &str, name: &str, path: &Path) -> String {
+    value
+        .replace("${name}", name)
+        .replace("${path}", &path.to_string_lossy())
+}
+
+/// `KEY=value` lines, with `#` comments, an optional `export` and optional matching quotes around
+/// the value.
+pub fn parse_dotenv(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|quote| {
+                    value
+                        .strip_prefix(*quote)
+                        .and_then(|v| v.strip_suffix(*quote))
+                })
+                .unwrap_or(value);
+
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use similar_asserts::assert_eq;
+
+    use super::{interpolate, parse_dotenv};
+
+    #[test]
+    fn should_parse_dotenv_lines() {
+        let env = parse_dotenv(
+            "# database
+DATABASE_URL=postgres://localhost/api
+export RUST_LOG = debug
+QUOTED=\"a value\"
+SINGLE='it''s'
+EMPTY=
+not a variable
+",
+        );
+
+        assert_eq!(
+            env,
+            vec![
+                (
+                    "DATABASE_URL".to_string(),
+                    "postgres://localhost/api".to_string()
+                ),
+                ("RUST_LOG".to_string(), "debug".to_string()),
+                ("QUOTED".to_string(), "a value".to_string()),
+                ("SINGLE".to_string(), "it''s".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_interpolate_project_name_and_path() {
+        assert_eq!(
+            interpolate("${path}/target/${name}.log", "api", Path::new("/code/api")),
+            "/code/api/target/api.log"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use crate::config::config_file::AxlConfig;
+
+/// Settings kept in the repository itself, next to the code they are for.
+const REPO_CONFIG_FILE: &str = ".axl.yml";
+
+#[derive(Deserialize, Default, Debug)]
+struct RepoConfig {
+    #[serde(default)]
+    tasks: BTreeMap<String, String>,
+}
+
+impl AxlConfig {
+    /// Tasks of the project rooted at `path`, the ones in its `.axl.yml` replace config file tasks
+    /// with the same name.
+    #[instrument(skip(self), err)]
+    pub fn tasks(&self, name: &str, path: &Path) -> Result<BTreeMap<String, String>> {
+        let mut tasks = self
+            .project(name, path)
+            .map(|p| p.tasks.clone())
+            .unwrap_or_default();
+
+        match fs::read_to_string(path.join(REPO_CONFIG_FILE)) {
+            Ok(contents) if !contents.trim().is_empty() => {
+                tasks.extend(serde_yaml::from_str::<RepoConfig>(&contents)?.tasks);
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                debug!("no {} in {}", REPO_CONFIG_FILE, path.to_string_lossy());
+            }
+            Err(err) => Err(err)?,
+        }
+
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+    use assert_fs::{prelude::*, TempDir};
+    use similar_asserts::assert_eq;
+
+    use crate::config::config_file::{AxlConfig, ProjectConfig};
+
+    #[test]
+    fn should_merge_repo_tasks_over_config_tasks() -> Result<()> {
+        let repo = TempDir::new()?;
+        repo.child(".axl.yml").write_str(
+            "tasks:
+    test: cargo nextest run
+    serve: cargo run -- serve",
+        )?;
+        let config = AxlConfig {
+            projects: vec![ProjectConfig {
+                name: "api".to_string(),
+                tasks: BTreeMap::from([
+                    ("test".to_string(), "cargo test".to_string()),
+                    ("lint".to_string(), "cargo clippy".to_string()),
+                ]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.tasks("api", repo.path())?,
+            BTreeMap::from([
+                ("lint".to_string(), "cargo clippy".to_string()),
+                ("serve".to_string(), "cargo run -- serve".to_string()),
+                ("test".to_string(), "cargo nextest run".to_string()),
+            ])
+        );
+        assert_eq!(
+            config.tasks("docs", &repo.path().join("docs"))?,
+            BTreeMap::new()
+        );
+
+        Ok(())
+    }
+}
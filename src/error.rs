@@ -5,6 +5,18 @@ pub enum Error {
     #[error("could not find any sessions to choose from")]
     NoSessionsFound,
 
+    #[error("no previously visited session is running")]
+    NoPreviousSession,
+
+    #[error("session {0} is not running")]
+    SessionNotRunning(String),
+
+    #[error("could not share the session: {0}")]
+    ShareFailed(String),
+
+    #[error("axl {0} has to be run inside a tmux session")]
+    NotInSession(String),
+
     #[error("could not create a session")]
     CouldNotCreateSession,
 
@@ -13,4 +25,57 @@ pub enum Error {
 
     #[error("path {0} does not exist")]
     PathDoesNotExist(String),
+
+    #[error("can not switch to session {0} from inside the current multiplexer session")]
+    CannotSwitchSession(String),
+
+    #[error("layout {0} is not defined in the config file")]
+    LayoutNotFound(String),
+
+    #[error("tmux command failed: {0}")]
+    TmuxCommandFailed(String),
+
+    #[error("zellij command failed: {0}")]
+    ZellijCommandFailed(String),
+
+    #[error("unexpected output from tmux: {0}")]
+    UnexpectedTmuxOutput(String),
+
+    #[error("tmux control mode client disconnected")]
+    ControlModeDisconnected,
+
+    #[error("command can not be sent over tmux control mode")]
+    ControlModeUnsupported,
+
+    #[error("command did not succeed in {0} sessions")]
+    ExecFailed(usize),
+
+    #[error("no running session was started in {0}")]
+    NoSessionForPath(String),
+
+    #[error("no shell history has been recorded for {0}")]
+    NoShellHistory(String),
+
+    #[error("no tasks are defined for {0}")]
+    NoTasksFound(String),
+
+    #[error("task {0} is not defined for {1}")]
+    TaskNotFound(String, String),
+
+    #[error("task {0} exited with status {1}")]
+    TaskFailed(String, i32),
+
+    #[error("task {0} was killed before it reported its exit status")]
+    TaskKilled(String),
+
+    #[error("{feature} needs {tool} {required} or newer, found {found}")]
+    VersionTooOld {
+        feature: String,
+        tool: String,
+        required: String,
+        found: String,
+    },
+
+    #[error("hook '{0}' {1}")]
+    HookFailed(String, String),
 }
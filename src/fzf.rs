@@ -3,10 +3,23 @@ use colored::Colorize;
 use std::{
     fmt::{Debug, Display},
     io::Write,
-    process::{Command, Stdio},
+    process::{Command, Output, Stdio},
+    sync::OnceLock,
 };
 use thiserror::Error;
-use tracing::instrument;
+use tracing::{debug, instrument};
+
+use crate::{
+    helper::wrap_command,
+    tmux::{version::TmuxFeature, TmuxCmd},
+    version::Version,
+};
+
+/// The first release with `--tmux`.
+pub const FZF_TMUX_VERSION: Version = Version::new(0, 53, 0);
+
+/// Read once per run, `None` when fzf could not be run or printed no version.
+static FZF_VERSION: OnceLock<Option<Version>> = OnceLock::new();
 
 #[derive(Error, Debug)]
 pub enum FzfError {
@@ -14,7 +27,7 @@ pub enum FzfError {
     NoItemsFound,
     #[error("no item selected from options")]
     NoItemSelected,
-    #[error("waiting on fzf command failed")]
+    #[error("fzf command failed: {0}")]
     CommandFailed(#[from] std::io::Error),
 }
 
@@ -24,6 +37,27 @@ pub struct FzfCmd;
 impl FzfCmd {
     const CMD: &'static str = "fzf";
 
+    pub fn version() -> Option<Version> {
+        *FZF_VERSION.get_or_init(|| {
+            let output = wrap_command(Command::new(Self::CMD).arg("--version")).ok()?;
+            let version = Version::parse(&String::from_utf8_lossy(&output.stdout));
+            debug!("fzf version: {version:?}");
+            version
+        })
+    }
+
+    /// `--tmux` when both fzf and tmux can open the picker in a popup, otherwise nothing so it
+    /// runs inline in the current terminal.
+    pub fn popup_args() -> &'static [&'static str] {
+        let popup = Self::version().is_some_and(|version| version >= FZF_TMUX_VERSION)
+            && TmuxCmd::supports(TmuxFeature::Popup);
+        if popup {
+            &["--tmux"]
+        } else {
+            &[]
+        }
+    }
+
     #[instrument()]
     pub fn find_vec<T>(input: Vec<T>) -> Result<String, FzfError>
     where
@@ -38,29 +72,7 @@ impl FzfCmd {
     /// Will always return the query-string if no item is selected.
     #[instrument()]
     pub fn find_string(input: &str) -> Result<String, FzfError> {
-        let mut fzf_child = Command::new(Self::CMD)
-            .arg("--tmux")
-            .arg("--print-query")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("fzf command should spawn");
-
-        // Get the stdin handle of the child process
-        fzf_child.stdin.as_mut().map_or_else(
-            || {
-                eprintln!("Failed to get stdin handle for the child process");
-            },
-            |stdin| {
-                // Write your input string to the command's stdin
-                stdin
-                    .write_all(input.as_bytes())
-                    .expect("should be able to pass project names to fzf stdin");
-            },
-        );
-
-        // Ensure the child process has finished
-        let output = fzf_child.wait_with_output()?;
+        let output = Self::run(&["--print-query"], input)?;
 
         let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
@@ -74,6 +86,42 @@ impl FzfCmd {
         Ok(result)
     }
 
+    /// Pick any number of items, an empty vec is returned when nothing is selected.
+    #[instrument()]
+    pub fn find_vec_multi<T>(input: Vec<T>) -> Result<Vec<String>, FzfError>
+    where
+        T: Debug + Display,
+    {
+        let items_string: String = input
+            .iter()
+            .fold(String::new(), |acc, item| format!("{acc}\n{item}"));
+
+        let output = Self::run(&["--multi"], items_string.trim_start())?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+            .collect())
+    }
+
+    /// Run fzf with `args` on the lines in `input` and wait for the pick.
+    fn run(args: &[&str], input: &str) -> Result<Output, FzfError> {
+        let mut fzf_child = Command::new(Self::CMD)
+            .args(Self::popup_args())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        if let Some(stdin) = fzf_child.stdin.as_mut() {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        // Closes stdin first, so fzf sees the end of the list.
+        Ok(fzf_child.wait_with_output()?)
+    }
+
     #[instrument(err)]
     pub fn pick_one_filtered(&mut self, items: Vec<String>) -> Result<String> {
         if items.is_empty() {
@@ -2,12 +2,15 @@ use std::{
     fs,
     path::{Path, PathBuf},
     process::{Command, Output, Stdio},
+    time::Duration,
 };
 
 use anyhow::Result;
 use clap::ValueEnum;
 use tracing::{instrument, trace, warn};
 
+use crate::config::config_env::ConfigEnvKey;
+
 #[derive(ValueEnum, Debug, Clone)]
 pub enum OutputFormat {
     /// rust debug print.
@@ -62,3 +65,221 @@ pub fn get_directories(path: &Path) -> Result<Vec<PathBuf>> {
         })
         .collect())
 }
+
+/// Match `name` against a shell style glob where `*` matches any run of characters and `?`
+/// matches exactly one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` seen, and the name position it was matched against.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Units accepted by [`parse_duration`] and used by [`format_duration`], largest first.
+const DURATION_UNITS: [(char, u64); 5] = [
+    ('w', 7 * 24 * 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('h', 60 * 60),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// A duration like `2w`, `3d`, `12h`, `30m` or `45s`, a plain number is seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, seconds) = DURATION_UNITS
+        .iter()
+        .find_map(|(unit, seconds)| Some((value.strip_suffix(*unit)?, *seconds)))
+        .unwrap_or((value, 1));
+
+    number
+        .parse::<u64>()
+        .map(|n| Duration::from_secs(n * seconds))
+        .map_err(|_| format!("expected a number followed by one of w, d, h, m or s, got '{value}'"))
+}
+
+/// `duration` in its largest whole unit, like `3d` for 80 hours.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    DURATION_UNITS
+        .iter()
+        .find(|(_, seconds)| secs >= *seconds)
+        .map_or_else(
+            || "0s".to_string(),
+            |(unit, seconds)| format!("{}{unit}", secs / seconds),
+        )
+}
+
+/// Single quote `value` for a POSIX shell.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Join `args` into a command line for a POSIX shell, quoting the ones a shell would split or
+/// expand so each arrives as the same argument.
+pub fn shell_join(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+            {
+                arg.clone()
+            } else {
+                shell_quote(arg)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The command line `args` stand for: a single argument is taken as a whole command line so
+/// `'git pull && make'` keeps its operators, several are joined with [`shell_join`].
+pub fn command_line(args: &[String]) -> String {
+    match args {
+        [line] => line.clone(),
+        args => shell_join(args),
+    }
+}
+
+/// Seconds since the unix epoch as a compact UTC timestamp that sorts by time, like
+/// `20240131T235959Z`.
+pub fn utc_timestamp(secs: u64) -> String {
+    let (days, time) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01, from Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// `$XDG_STATE_HOME/axl`, created if it does not exist yet.
+#[instrument(err)]
+pub fn state_dir() -> Result<PathBuf> {
+    let mut path = PathBuf::try_from(ConfigEnvKey::XDGStateHome)?;
+    path.push("axl");
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// `$XDG_DATA_HOME/axl`, created if it does not exist yet.
+#[instrument(err)]
+pub fn data_dir() -> Result<PathBuf> {
+    let mut path = PathBuf::try_from(ConfigEnvKey::XDGDataHome)?;
+    path.push("axl");
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use std::time::Duration;
+
+    use super::{
+        command_line, format_duration, glob_match, parse_duration, shell_join, utc_timestamp,
+    };
+
+    #[rstest]
+    #[case::exact("api", "api", true)]
+    #[case::exact_mismatch("api", "api2", false)]
+    #[case::star_suffix("api*", "api-v2", true)]
+    #[case::star_prefix("*_scratch", "api_scratch", true)]
+    #[case::star_middle("a*t", "axolotl_api", false)]
+    #[case::star_middle_match("a*i", "axolotl_gi", true)]
+    #[case::star_empty("*", "", true)]
+    #[case::question("ap?", "api", true)]
+    #[case::question_too_short("ap??", "api", false)]
+    #[case::multiple_stars("*o*l*", "axolotl", true)]
+    fn should_match_globs(#[case] pattern: &str, #[case] name: &str, #[case] expected: bool) {
+        assert_eq!(glob_match(pattern, name), expected);
+    }
+
+    #[rstest]
+    #[case("2w", Some(14 * 24 * 60 * 60))]
+    #[case("3d", Some(3 * 24 * 60 * 60))]
+    #[case("12h", Some(12 * 60 * 60))]
+    #[case("30m", Some(30 * 60))]
+    #[case("45", Some(45))]
+    #[case("d", None)]
+    #[case("1.5h", None)]
+    fn should_parse_durations(#[case] value: &str, #[case] expected: Option<u64>) {
+        assert_eq!(
+            parse_duration(value).ok(),
+            expected.map(Duration::from_secs)
+        );
+    }
+
+    #[test]
+    fn should_format_durations_in_largest_unit() {
+        assert_eq!(format_duration(Duration::from_secs(80 * 60 * 60)), "3d");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+
+    #[rstest]
+    #[case(0, "19700101T000000Z")]
+    #[case(951_782_400, "20000229T000000Z")]
+    #[case(1_706_745_599, "20240131T235959Z")]
+    fn should_format_utc_timestamps(#[case] secs: u64, #[case] expected: &str) {
+        assert_eq!(utc_timestamp(secs), expected);
+    }
+
+    #[test]
+    fn should_quote_arguments_only_when_needed() {
+        let args = ["echo", "a b", "it's", "", "--flag=x/y", "$HOME"].map(String::from);
+
+        assert_eq!(
+            shell_join(&args),
+            r"echo 'a b' 'it'\''s' '' --flag=x/y '$HOME'"
+        );
+    }
+
+    #[rstest]
+    #[case(&["git pull && make"], "git pull && make")]
+    #[case(&["echo", "a && b"], "echo 'a && b'")]
+    fn should_only_quote_several_arguments(#[case] args: &[&str], #[case] expected: &str) {
+        let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(command_line(&args), expected);
+    }
+}
@@ -0,0 +1,143 @@
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::{helper::state_dir, multiplexer::SessionName};
+
+const HISTORY_FILE: &str = "history.json";
+const HISTORY_LIMIT: usize = 100;
+
+/// Sessions in the order they were last visited, stored in `$XDG_STATE_HOME/axl/history.json`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct SessionHistory {
+    /// Most recent first.
+    pub sessions: Vec<SessionName>,
+}
+
+impl SessionHistory {
+    /// The stored history, empty when nothing has been recorded yet.
+    #[instrument(err)]
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(Self::path()?) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err)?,
+        }
+    }
+
+    #[instrument(err)]
+    pub fn save(&self) -> Result<()> {
+        // Written next to the real file and renamed, so hooks running at the same time never
+        // read half a file.
+        let path = Self::path()?;
+        let tmp_path = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp_path, serde_json::to_string(self)?)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Record a visit to `name`, a failure is only logged since it should never stop a switch.
+    #[instrument]
+    pub fn record(name: &SessionName) {
+        // Scratchpads are visited through popups, switching back to one would be surprising.
+        if name.is_scratchpad() {
+            return;
+        }
+        let recorded = Self::load().and_then(|mut history| {
+            history.visit(name);
+            history.save()
+        });
+        if let Err(err) = recorded {
+            warn!("could not record session history: {err:?}");
+        }
+    }
+
+    pub fn visit(&mut self, name: &SessionName) {
+        self.sessions.retain(|s| s != name);
+        self.sessions.insert(0, name.clone());
+        self.sessions.truncate(HISTORY_LIMIT);
+    }
+
+    /// Order `sessions` by when they were last visited, with sessions that were never visited
+    /// after them and `current` at the very end since it is the one least likely to be picked.
+    pub fn sort(&self, sessions: &mut [SessionName], current: &SessionName) {
+        sessions.sort_by_key(|s| {
+            (
+                s == current,
+                self.sessions
+                    .iter()
+                    .position(|h| h == s)
+                    .unwrap_or(usize::MAX),
+            )
+        });
+    }
+
+    /// The most recently visited of the `running` sessions other than `current`.
+    pub fn previous<'a>(
+        &self,
+        current: &SessionName,
+        running: &'a [SessionName],
+    ) -> Option<&'a SessionName> {
+        self.sessions
+            .iter()
+            .filter(|s| *s != current)
+            .find_map(|s| running.iter().find(|r| *r == s))
+    }
+
+    fn path() -> Result<PathBuf> {
+        Ok(state_dir()?.join(HISTORY_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::SessionHistory;
+    use crate::multiplexer::SessionName;
+
+    fn names(names: &[&str]) -> Vec<SessionName> {
+        names.iter().map(|n| SessionName::new(n)).collect()
+    }
+
+    #[test]
+    fn should_move_visited_sessions_to_the_front() {
+        let mut history = SessionHistory::default();
+
+        for name in ["api", "docs", "web", "api"] {
+            history.visit(&SessionName::new(name));
+        }
+
+        assert_eq!(history.sessions, names(&["api", "web", "docs"]));
+    }
+
+    #[test]
+    fn should_sort_by_recency_with_current_last() {
+        let history = SessionHistory {
+            sessions: names(&["api", "web", "docs"]),
+        };
+        let mut sessions = names(&["docs", "new", "api", "web"]);
+
+        history.sort(&mut sessions, &SessionName::new("api"));
+
+        assert_eq!(sessions, names(&["web", "docs", "new", "api"]));
+    }
+
+    #[test]
+    fn should_find_previous_running_session() {
+        let history = SessionHistory {
+            sessions: names(&["api", "killed", "docs"]),
+        };
+
+        assert_eq!(
+            history.previous(&SessionName::new("api"), &names(&["docs", "api"])),
+            Some(&SessionName::new("docs"))
+        );
+        assert_eq!(
+            history.previous(&SessionName::new("api"), &names(&["api"])),
+            None
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+use colored::Colorize;
+use strum::Display;
+use tracing::{error, info, instrument};
+
+use crate::{
+    config::config_file::{AxlConfig, HooksConfig},
+    error::Error,
+    multiplexer::SessionName,
+};
+
+pub const SESSION_NAME_KEY: &str = "AXL_SESSION_NAME";
+pub const SESSION_PATH_KEY: &str = "AXL_SESSION_PATH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Hook {
+    OnCreate,
+    OnAttach,
+    OnKill,
+}
+
+impl HooksConfig {
+    pub const fn command(&self, hook: Hook) -> Option<&String> {
+        match hook {
+            Hook::OnCreate => self.on_create.as_ref(),
+            Hook::OnAttach => self.on_attach.as_ref(),
+            Hook::OnKill => self.on_kill.as_ref(),
+        }
+    }
+}
+
+/// Run the global command for `hook`, then the one from the session's project.
+///
+/// A failing command does not stop the session from opening or being killed, it is printed and
+/// followed by the `on_error` behavior instead.
+#[instrument(skip(config), err)]
+pub fn run_hooks(
+    hook: Hook,
+    name: &SessionName,
+    path: Option<&Path>,
+    config: &AxlConfig,
+) -> Result<()> {
+    let project = config.project(&name.to_string(), path.unwrap_or_else(|| Path::new("")));
+    let path = path.or_else(|| project.and_then(|p| p.path.as_deref()));
+
+    for command in [Some(&config.hooks), project.map(|p| &p.hooks)]
+        .into_iter()
+        .flatten()
+        .filter_map(|hooks| hooks.command(hook))
+    {
+        info!("running {hook} hook for {name}: {command}");
+        if let Err(err) = run_hook(command, name, path) {
+            let msg = format!("[HOOK ERROR]: {hook} for {name}: {err:#}");
+            error!(msg);
+            eprintln!("{}", msg.red().bold());
+            config.general.on_error.wait()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_hook(command: &str, name: &SessionName, path: Option<&Path>) -> Result<()> {
+    let mut hook = Command::new("sh");
+    hook.arg("-c")
+        .arg(command)
+        .env(SESSION_NAME_KEY, name.as_str())
+        .stdin(Stdio::null());
+    if let Some(path) = path {
+        hook.env(SESSION_PATH_KEY, path).current_dir(path);
+    }
+
+    let status = hook.status()?;
+    if !status.success() {
+        Err(Error::HookFailed(command.to_string(), status.to_string()))?;
+    }
+    Ok(())
+}
@@ -2,5 +2,13 @@ pub mod config;
 pub mod error;
 pub mod fzf;
 pub mod helper;
+pub mod history;
+pub mod hooks;
+pub mod multiplexer;
+pub mod record;
+pub mod shell_history;
+pub mod status;
 pub mod tmux;
+pub mod version;
+pub mod zellij;
 pub mod zoxide;
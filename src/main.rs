@@ -1,10 +1,8 @@
 use anyhow::Result;
-use axl_lib::config::config_file::OnError;
 use clap::Parser;
 use cli::Cli;
 use colored::Colorize;
-use inquire::Text;
-use std::{env, process::exit, time::Duration};
+use std::{env, process::exit};
 use tracing::{error, info_span};
 use tracing_log::AsTrace;
 use uuid::Uuid;
@@ -47,18 +45,7 @@ fn main() -> Result<()> {
                     let msg = format!("[CMD ERROR]: {err:?}");
                     error!(run.uuid = trace_uuid.to_string(), msg,);
                     eprintln!("{}", msg.red().bold());
-                    match cli.args.on_error {
-                        OnError::None => {}
-                        OnError::Pause => {
-                            Text::new("Press ENTER to continue...").prompt()?;
-                        }
-                        OnError::ShortDelay => {
-                            std::thread::sleep(Duration::from_millis(500));
-                        }
-                        OnError::LongDelay => {
-                            std::thread::sleep(Duration::from_millis(5000));
-                        }
-                    }
+                    cli.ctx.config.general.on_error.wait()?;
                     exit(1)
                 }
             },
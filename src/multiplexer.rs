@@ -0,0 +1,317 @@
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use tracing::instrument;
+
+use crate::{config::config_file::AxlConfig, tmux::TmuxCmd, zellij::ZellijCmd};
+
+/// Characters that tmux rejects or reads as target syntax in a session name, they are escaped as
+/// `%XX` along with `%` itself and control characters.
+const ESCAPED_CHARS: [char; 7] = ['.', ':', '%', '$', '^', '\'', '!'];
+
+/// Ends the name of the hidden session `axl popup` keeps for a project. [`SessionName::new`]
+/// only writes `%` before two hex digits, so no name it builds can end this way.
+const SCRATCHPAD_SUFFIX: &str = "%__scratch";
+
+/// A session name as the multiplexer stores it.
+///
+/// Names are built from what the user sees with [`SessionName::new`], which escapes characters
+/// the multiplexer can not take, and shown with [`Display`], which turns them back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct SessionName(String);
+
+impl SessionName {
+    pub fn new(name: &str) -> Self {
+        let mut encoded = String::with_capacity(name.len());
+        for c in name.chars() {
+            if ESCAPED_CHARS.contains(&c) || c.is_control() {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    encoded.push_str(&format!("%{byte:02X}"));
+                }
+            } else {
+                encoded.push(c);
+            }
+        }
+        Self(encoded)
+    }
+
+    /// Wrap a name read back from the multiplexer, which is already encoded.
+    pub fn from_encoded(name: &str) -> Self {
+        Self(name.to_string())
+    }
+
+    /// Name of the session for the project at `path`, the directory name when it is free or
+    /// already used for `path`, otherwise parent directories are added until it is unique.
+    ///
+    /// `existing` holds running sessions and their paths, sessions without a known path are
+    /// assumed to belong to `path`.
+    pub fn for_path(path: &Path, existing: &[(Self, Option<PathBuf>)]) -> Self {
+        let components: Vec<_> = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect();
+
+        let taken = |name: &Self| {
+            existing.iter().any(|(other, other_path)| {
+                other == name && other_path.as_deref().is_some_and(|p| p != path)
+            })
+        };
+
+        (1..=components.len())
+            .map(|n| Self::new(&components[components.len() - n..].join("/")))
+            .find(|name| !taken(name))
+            .unwrap_or_else(|| Self::new(&path.to_string_lossy()))
+    }
+
+    /// The hidden `<name>%__scratch` session that `axl popup` opens for this one.
+    pub fn scratchpad(&self) -> Self {
+        Self(format!("{}{SCRATCHPAD_SUFFIX}", self.0))
+    }
+
+    /// Whether this is another session's scratchpad, which is left out of session lists.
+    pub fn is_scratchpad(&self) -> bool {
+        self.0.ends_with(SCRATCHPAD_SUFFIX)
+    }
+
+    /// The encoded name, which is what the multiplexer knows the session as.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A name typed by the user, like a command line argument, see [`SessionName::new`].
+impl FromStr for SessionName {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(name))
+    }
+}
+
+impl Display for SessionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = Vec::with_capacity(self.0.len());
+        let mut rest = self.0.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            let escaped = (byte == b'%')
+                .then(|| tail.get(..2))
+                .flatten()
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(decoded) => {
+                    bytes.push(decoded);
+                    rest = &tail[2..];
+                }
+                None => {
+                    bytes.push(byte);
+                    rest = tail;
+                }
+            }
+        }
+        write!(f, "{}", String::from_utf8_lossy(&bytes))
+    }
+}
+
+/// Session operations that every supported terminal multiplexer must provide.
+pub trait Multiplexer {
+    /// All sessions known to the multiplexer.
+    fn list_sessions() -> Result<Vec<SessionName>>;
+
+    /// Sessions with the directory they were started in, when the multiplexer reports it.
+    fn list_session_paths() -> Result<Vec<(SessionName, Option<PathBuf>)>>;
+
+    /// Open the session `name` rooted at `path`, creating it with the project's settings from
+    /// `config` if needed.
+    fn open(path: &Path, name: &SessionName, config: &AxlConfig) -> Result<()>;
+
+    /// Switch to (or attach to) an already running session.
+    fn open_existing(name: &SessionName, config: &AxlConfig) -> Result<()>;
+
+    /// Move the current client to the running session `name`, or attach to it from outside the
+    /// multiplexer, without running hooks or recording it in the session history.
+    fn switch(name: &SessionName) -> Result<()>;
+
+    /// Kill every session in `sessions`, killing `current_session` last, and return the ones that
+    /// were killed. A session that could not be killed is logged and skipped.
+    fn kill_sessions(
+        sessions: &[SessionName],
+        current_session: &SessionName,
+        config: &AxlConfig,
+    ) -> Result<Vec<SessionName>>;
+
+    fn has_session(name: &SessionName) -> bool;
+
+    fn get_current_session() -> SessionName;
+}
+
+#[derive(
+    Serialize, Deserialize, Default, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Display,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Multiplexers {
+    #[default]
+    Tmux,
+    Zellij,
+}
+
+impl Multiplexers {
+    #[instrument(err)]
+    pub fn list_sessions(&self) -> Result<Vec<SessionName>> {
+        match self {
+            Self::Tmux => TmuxCmd::list_sessions(),
+            Self::Zellij => ZellijCmd::list_sessions(),
+        }
+    }
+
+    #[instrument(err)]
+    pub fn list_session_paths(&self) -> Result<Vec<(SessionName, Option<PathBuf>)>> {
+        match self {
+            Self::Tmux => TmuxCmd::list_session_paths(),
+            Self::Zellij => ZellijCmd::list_session_paths(),
+        }
+    }
+
+    #[instrument(skip(config), err)]
+    pub fn open(&self, path: &Path, name: &SessionName, config: &AxlConfig) -> Result<()> {
+        match self {
+            Self::Tmux => TmuxCmd::open(path, name, config),
+            Self::Zellij => ZellijCmd::open(path, name, config),
+        }
+    }
+
+    #[instrument(skip(config), err)]
+    pub fn open_existing(&self, name: &SessionName, config: &AxlConfig) -> Result<()> {
+        match self {
+            Self::Tmux => TmuxCmd::open_existing(name, config),
+            Self::Zellij => ZellijCmd::open_existing(name, config),
+        }
+    }
+
+    #[instrument(err)]
+    pub fn switch(&self, name: &SessionName) -> Result<()> {
+        match self {
+            Self::Tmux => TmuxCmd::switch(name),
+            Self::Zellij => ZellijCmd::switch(name),
+        }
+    }
+
+    #[instrument(skip(config), err)]
+    pub fn kill_sessions(
+        &self,
+        sessions: &[SessionName],
+        current_session: &SessionName,
+        config: &AxlConfig,
+    ) -> Result<Vec<SessionName>> {
+        match self {
+            Self::Tmux => TmuxCmd::kill_sessions(sessions, current_session, config),
+            Self::Zellij => ZellijCmd::kill_sessions(sessions, current_session, config),
+        }
+    }
+
+    #[instrument]
+    pub fn has_session(&self, name: &SessionName) -> bool {
+        match self {
+            Self::Tmux => TmuxCmd::has_session(name),
+            Self::Zellij => ZellijCmd::has_session(name),
+        }
+    }
+
+    #[instrument]
+    pub fn get_current_session(&self) -> SessionName {
+        match self {
+            Self::Tmux => TmuxCmd::get_current_session(),
+            Self::Zellij => ZellijCmd::get_current_session(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::SessionName;
+
+    #[rstest]
+    #[case("axolotl_git", "axolotl_git")]
+    #[case("my.project", "my%2Eproject")]
+    #[case("50%: done!", "50%25%3A done%21")]
+    #[case("it's $HOME^", "it%27s %24HOME%5E")]
+    #[case("tab\there", "tab%09here")]
+    fn should_encode_session_names_reversibly(#[case] name: &str, #[case] encoded: &str) {
+        let session_name = SessionName::new(name);
+
+        assert_eq!(session_name.as_str(), encoded);
+        assert_eq!(session_name.to_string(), name);
+    }
+
+    #[test]
+    fn should_parse_typed_names_like_new() {
+        let parsed: SessionName = "my.project".parse().expect("names always parse");
+
+        assert_eq!(parsed, SessionName::new("my.project"));
+        assert_eq!(parsed.as_str(), "my%2Eproject");
+    }
+
+    #[test]
+    fn should_name_scratchpads_after_their_session() {
+        let scratchpad = SessionName::new("api.v2").scratchpad();
+
+        assert_eq!(scratchpad.as_str(), "api%2Ev2%__scratch");
+        assert_eq!(scratchpad.to_string(), "api.v2%__scratch");
+        assert!(scratchpad.is_scratchpad());
+        assert!(!SessionName::new("api.v2").is_scratchpad());
+        assert!(!SessionName::new("api__scratch").is_scratchpad());
+        assert!(!SessionName::new("api%__scratch").is_scratchpad());
+    }
+
+    #[test]
+    fn should_decode_names_not_created_by_axl() {
+        assert_eq!(SessionName::from_encoded("100%").to_string(), "100%");
+        assert_eq!(SessionName::from_encoded("a%zzb").to_string(), "a%zzb");
+    }
+
+    #[test]
+    fn should_add_parent_directories_on_collision() {
+        let existing = vec![
+            (
+                SessionName::new("api"),
+                Some(PathBuf::from("/code/work/api")),
+            ),
+            (SessionName::new("docs"), None),
+        ];
+
+        assert_eq!(
+            SessionName::for_path(Path::new("/code/work/api"), &existing),
+            SessionName::new("api")
+        );
+        assert_eq!(
+            SessionName::for_path(Path::new("/code/home/api"), &existing),
+            SessionName::new("home/api")
+        );
+        assert_eq!(
+            SessionName::for_path(Path::new("/code/docs"), &existing),
+            SessionName::new("docs")
+        );
+        assert_eq!(
+            SessionName::for_path(Path::new("/code/axolotl.git"), &existing).as_str(),
+            "axolotl%2Egit"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::instrument;
+
+use crate::{helper::state_dir, multiplexer::SessionName};
+
+const RECORDINGS_DIR: &str = "recordings";
+
+/// How pane output is written to a recording.
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Exactly what the pane printed, escape codes included.
+    #[default]
+    Raw,
+    /// Text with the escape codes stripped.
+    Plain,
+    /// asciicast v2, replayable with `asciinema play`.
+    Asciicast,
+}
+
+impl RecordFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Raw => "log",
+            Self::Plain => "txt",
+            Self::Asciicast => "cast",
+        }
+    }
+}
+
+/// A file in `$XDG_STATE_HOME/axl/recordings/<session>/`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub session: SessionName,
+    pub path: PathBuf,
+    /// The pane id (`%<n>`) the output came from.
+    pub pane: String,
+    pub bytes: u64,
+    /// Whether the pane is still being recorded to this file.
+    pub active: bool,
+}
+
+/// Directory holding the recordings of `session`, created if it does not exist yet.
+#[instrument(err)]
+pub fn recordings_dir(session: &SessionName) -> Result<PathBuf> {
+    let path = state_dir()?.join(RECORDINGS_DIR).join(session.as_str());
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// File name for a new recording of a pane, named `<timestamp>-<window>.<pane>-<pane id>` so
+/// they sort by when they were started.
+pub fn recording_file_name(
+    timestamp: &str,
+    window_index: u32,
+    pane_index: u32,
+    pane_id: &str,
+    format: RecordFormat,
+) -> String {
+    format!(
+        "{timestamp}-{window_index}.{pane_index}-{pane_id}.{}",
+        format.extension()
+    )
+}
+
+/// Every recording, oldest first within each session. `active` are the files panes are being
+/// recorded to right now.
+#[instrument(err)]
+pub fn recordings(active: &[PathBuf]) -> Result<Vec<Recording>> {
+    let root = state_dir()?.join(RECORDINGS_DIR);
+    let sessions = match fs::read_dir(&root) {
+        Ok(sessions) => sessions,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => Err(err)?,
+    };
+
+    let mut recordings = Vec::new();
+    for session_dir in sessions {
+        let session_dir = session_dir?;
+        let session = SessionName::from_encoded(&session_dir.file_name().to_string_lossy());
+        let mut files: Vec<PathBuf> = fs::read_dir(session_dir.path())?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        files.sort();
+
+        for path in files {
+            let Some(pane) = path.file_stem().and_then(|stem| {
+                stem.to_string_lossy()
+                    .rsplit_once('-')
+                    .map(|(_, id)| id.to_string())
+            }) else {
+                continue;
+            };
+            recordings.push(Recording {
+                session: session.clone(),
+                bytes: fs::metadata(&path)?.len(),
+                active: active.contains(&path),
+                pane,
+                path,
+            });
+        }
+    }
+
+    Ok(recordings)
+}
+
+/// Removes escape sequences from terminal output, keeping state between chunks since a
+/// sequence can be split across reads.
+#[derive(Debug, Default)]
+pub struct AnsiStripper {
+    state: StripState,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum StripState {
+    #[default]
+    Text,
+    /// After `ESC`.
+    Escape,
+    /// In a `ESC [` control sequence, which ends with a byte in `@`..=`~`.
+    Csi,
+    /// In a `ESC ]` (or `P`, `_`, `^`) string, which ends with `BEL` or `ESC \`.
+    String,
+    /// After `ESC` inside a string.
+    StringEscape,
+}
+
+impl AnsiStripper {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+
+    pub fn strip(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(input.len());
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (StripState::Text, Self::ESC) => StripState::Escape,
+                // Carriage returns only move the cursor, new lines are kept.
+                (StripState::Text, b'\r' | Self::BEL) => StripState::Text,
+                (StripState::Text, _) => {
+                    output.push(byte);
+                    StripState::Text
+                }
+                (StripState::Escape, b'[') => StripState::Csi,
+                (StripState::Escape, b']' | b'P' | b'_' | b'^') => StripState::String,
+                // Two byte sequences like `ESC =` or `ESC 7`.
+                (StripState::Escape, _) => StripState::Text,
+                (StripState::Csi, 0x40..=0x7e) => StripState::Text,
+                (StripState::Csi, _) => StripState::Csi,
+                (StripState::String, Self::BEL) => StripState::Text,
+                (StripState::String, Self::ESC) => StripState::StringEscape,
+                (StripState::String, _) => StripState::String,
+                (StripState::StringEscape, b'\\') => StripState::Text,
+                (StripState::StringEscape, _) => StripState::String,
+            };
+        }
+        output
+    }
+}
+
+/// Writes output as asciicast v2 events, holding back a character split across chunks since
+/// each event has to be valid UTF-8.
+#[derive(Debug)]
+pub struct AsciicastWriter {
+    started: Instant,
+    pending: Vec<u8>,
+}
+
+impl AsciicastWriter {
+    /// The header line, written once at the start of the file.
+    pub fn header(width: u32, height: u32, title: &str) -> Result<String> {
+        Ok(json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            "title": title,
+        })
+        .to_string())
+    }
+
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// An output event line for `chunk`, `None` when it only holds part of a character.
+    pub fn event(&mut self, chunk: &[u8]) -> Option<String> {
+        self.pending.extend_from_slice(chunk);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // An incomplete character at the end, the rest arrives with the next chunk.
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if valid == 0 {
+            return None;
+        }
+
+        let data: Vec<u8> = self.pending.drain(..valid).collect();
+        Some(
+            json!([
+                self.started.elapsed().as_secs_f64(),
+                "o",
+                String::from_utf8_lossy(&data)
+            ])
+            .to_string(),
+        )
+    }
+}
+
+impl Default for AsciicastWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copy `input` to the end of `output` in `format` until it is closed, run by `pipe-pane`.
+#[instrument(skip(input), err)]
+pub fn record_sink(
+    input: &mut impl Read,
+    output: &Path,
+    format: RecordFormat,
+    size: (u32, u32),
+    title: &str,
+) -> Result<()> {
+    let mut file: File = OpenOptions::new().create(true).append(true).open(output)?;
+    let mut stripper = AnsiStripper::default();
+    let mut asciicast = AsciicastWriter::new();
+    if format == RecordFormat::Asciicast && file.metadata()?.len() == 0 {
+        writeln!(file, "{}", AsciicastWriter::header(size.0, size.1, title)?)?;
+    }
+
+    let mut buffer = [0; 8192];
+    loop {
+        let read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => Err(err)?,
+        };
+        let chunk = &buffer[..read];
+        match format {
+            RecordFormat::Raw => file.write_all(chunk)?,
+            RecordFormat::Plain => file.write_all(&stripper.strip(chunk))?,
+            RecordFormat::Asciicast => {
+                if let Some(event) = asciicast.event(chunk) {
+                    writeln!(file, "{event}")?;
+                }
+            }
+        }
+        file.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::{AnsiStripper, AsciicastWriter};
+
+    #[test]
+    fn should_strip_escape_codes_across_chunks() {
+        let mut stripper = AnsiStripper::default();
+
+        let mut output = stripper.strip(b"\x1b[1;32mok\x1b[0");
+        output.extend(stripper.strip(b"m done\r\n\x1b]0;title\x07\x1b=next\x1b]8;;url\x1b\\"));
+
+        assert_eq!(String::from_utf8_lossy(&output), "ok done\nnext");
+    }
+
+    #[test]
+    fn should_hold_back_split_characters() {
+        let mut writer = AsciicastWriter::new();
+        let arrow = "→".as_bytes();
+
+        let first = writer.event(&[b"a".as_slice(), &arrow[..1]].concat());
+        let second = writer.event(&arrow[1..]);
+
+        assert!(first.is_some_and(|e| e.ends_with(",\"o\",\"a\"]")));
+        assert!(second.is_some_and(|e| e.ends_with(",\"o\",\"→\"]")));
+        assert_eq!(writer.event(&arrow[..2]), None);
+    }
+}
@@ -0,0 +1,106 @@
+use std::{cmp::Reverse, collections::HashSet, fs, path::PathBuf, time::SystemTime};
+
+use anyhow::Result;
+use tracing::instrument;
+
+use crate::{error::Error, helper::data_dir};
+
+const HISTORY_DIR: &str = "history";
+
+/// History file of a project with `isolated_history`, in `$XDG_DATA_HOME/axl/history/`.
+#[instrument(err)]
+pub fn histfile(project: &str) -> Result<PathBuf> {
+    let dir = data_dir()?.join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(project.replace('/', "_")))
+}
+
+/// Commands from the history of `project`, or of every project, most recent first and without
+/// duplicates. Across projects, the most recently written file comes first.
+#[instrument(err)]
+pub fn history_entries(project: Option<&str>) -> Result<Vec<String>> {
+    let files = match project {
+        Some(project) => vec![histfile(project)?],
+        None => {
+            let dir = data_dir()?.join(HISTORY_DIR);
+            let mut files: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+                .collect();
+            files.sort_by_key(|(modified, _)| Reverse(*modified));
+            files.into_iter().map(|(_, path)| path).collect()
+        }
+    };
+
+    let mut seen = HashSet::new();
+    let entries: Vec<String> = files
+        .iter()
+        .filter_map(|path| fs::read(path).ok())
+        // zsh writes some characters in its own encoding, those are not worth failing over.
+        .flat_map(|contents| parse_histfile(&String::from_utf8_lossy(&contents)))
+        .filter(|entry| seen.insert(entry.clone()))
+        .collect();
+
+    if entries.is_empty() {
+        Err(Error::NoShellHistory(
+            project.unwrap_or("any project").to_string(),
+        ))?;
+    }
+    Ok(entries)
+}
+
+/// Commands in a bash or zsh history file, most recent first. The timestamps written with bash's
+/// `HISTTIMEFORMAT` and zsh's `EXTENDED_HISTORY` are left out.
+pub fn parse_histfile(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .rev()
+        .filter(|line| {
+            !line
+                .strip_prefix('#')
+                .is_some_and(|t| !t.is_empty() && t.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|line| {
+            line.strip_prefix(": ")
+                .and_then(|rest| rest.split_once(';'))
+                .filter(|(meta, _)| {
+                    meta.split(':')
+                        .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                })
+                .map_or(line, |(_, command)| command)
+        })
+        .filter(|command| !command.trim().is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::parse_histfile;
+
+    #[test]
+    fn should_parse_bash_and_zsh_history() {
+        let bash = "cargo test
+#1700000000
+git status
+
+";
+        let zsh = ": 1700000000:0;cargo build
+: 1700000005:12;git commit -m 'a: b;c'
+: not a timestamp;ls";
+
+        assert_eq!(parse_histfile(bash), vec!["git status", "cargo test"]);
+        assert_eq!(
+            parse_histfile(zsh),
+            vec![
+                ": not a timestamp;ls",
+                "git commit -m 'a: b;c'",
+                "cargo build"
+            ]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tracing::{debug, instrument};
+
+use crate::{
+    config::constants::AxlColor,
+    helper::{state_dir, wrap_command},
+};
+
+const STATUS_CACHE_FILE: &str = "status-cache.json";
+
+/// Branch and working tree state of a repository.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct GitStatus {
+    /// The branch name, or the short commit id when detached.
+    pub branch: String,
+    /// Changed, staged and untracked files.
+    pub dirty: u32,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+impl GitStatus {
+    /// Status of the repository containing `path`, `None` when it is not in one.
+    #[instrument]
+    pub fn read(path: &Path) -> Option<Self> {
+        let output = wrap_command(
+            Command::new("git")
+                .arg("-C")
+                .arg(path)
+                .arg("status")
+                .arg("--porcelain=v2")
+                .arg("--branch"),
+        )
+        .ok()
+        .filter(|o| o.status.success())?;
+
+        Some(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parse `git status --porcelain=v2 --branch` output.
+    pub fn parse(porcelain: &str) -> Self {
+        let mut status = Self::default();
+        let mut oid = "";
+
+        for line in porcelain.lines() {
+            if let Some(header) = line.strip_prefix("# ") {
+                match header.split_once(' ') {
+                    Some(("branch.oid", value)) => oid = value,
+                    Some(("branch.head", value)) => status.branch = value.to_string(),
+                    Some(("branch.ab", value)) => {
+                        for count in value.split(' ') {
+                            if let Some(ahead) = count.strip_prefix('+') {
+                                status.ahead = ahead.parse().unwrap_or_default();
+                            } else if let Some(behind) = count.strip_prefix('-') {
+                                status.behind = behind.parse().unwrap_or_default();
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            } else if !line.is_empty() {
+                status.dirty += 1;
+            }
+        }
+
+        if status.branch == "(detached)" {
+            status.branch = oid.chars().take(7).collect();
+        }
+
+        status
+    }
+}
+
+/// Everything shown by `axl status`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct StatusSegment {
+    pub project: String,
+    pub git: Option<GitStatus>,
+    /// Other sessions with windows that have an activity, bell or silence alert.
+    pub activity: usize,
+}
+
+impl StatusSegment {
+    /// Fill in `template` as described on [`crate::config::config_file::StatusConfig`].
+    pub fn render(&self, template: &str) -> String {
+        let count = |symbol: &str, n: usize| {
+            if n == 0 {
+                String::new()
+            } else {
+                format!(" {symbol}{n}")
+            }
+        };
+        let git = self.git.clone().unwrap_or_default();
+
+        let values = [
+            ("project", self.project.clone()),
+            ("branch", git.branch),
+            ("dirty", count("*", git.dirty as usize)),
+            ("ahead", count("↑", git.ahead as usize)),
+            ("behind", count("↓", git.behind as usize)),
+            ("activity", count("!", self.activity)),
+        ];
+
+        let mut rendered = template.to_string();
+        for (placeholder, value) in values {
+            // A `#` would start a tmux format or style.
+            rendered = rendered.replace(&format!("{{{placeholder}}}"), &value.replace('#', "##"));
+        }
+        for color in AxlColor::iter() {
+            rendered = rendered.replace(&format!("{{{color}}}"), &color.tmux_style());
+        }
+        rendered.replace("{reset}", "#[default]")
+    }
+}
+
+/// Rendered segments keyed by what they were rendered for, so several clients redrawing their
+/// status lines do not all run git.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+struct StatusCache {
+    /// Seconds since the unix epoch the segment was rendered at, and the segment.
+    entries: BTreeMap<String, (u64, String)>,
+}
+
+/// The segment cached for `key` if it is younger than `max_age` seconds, otherwise the one
+/// `render` returns, which is then cached.
+#[instrument(skip(render), err)]
+pub fn cached_segment(
+    key: &str,
+    max_age: u64,
+    render: impl FnOnce() -> Result<String>,
+) -> Result<String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = cache_path()?;
+
+    let mut cache: StatusCache = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+        Err(err) if err.kind() == ErrorKind::NotFound => StatusCache::default(),
+        Err(err) => Err(err)?,
+    };
+
+    if let Some((rendered_at, segment)) = cache.entries.get(key) {
+        if now.saturating_sub(*rendered_at) < max_age {
+            debug!("using status segment cached at {rendered_at}");
+            return Ok(segment.clone());
+        }
+    }
+
+    let segment = render()?;
+    cache
+        .entries
+        .retain(|_, (rendered_at, _)| now.saturating_sub(*rendered_at) < max_age);
+    cache
+        .entries
+        .insert(key.to_string(), (now, segment.clone()));
+
+    // Renamed into place so a status line drawn at the same time never reads half a file.
+    let tmp_path = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp_path, serde_json::to_string(&cache)?)?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(segment)
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join(STATUS_CACHE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::{GitStatus, StatusSegment};
+
+    #[test]
+    fn should_parse_porcelain_status() {
+        let status = GitStatus::parse(
+            "# branch.oid 0e337c3a9d0c1f6b2b8e4a2f3c1d5e6f7a8b9c0d
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -1
+1 .M N... 100644 100644 100644 3f2a 3f2a src/cli.rs
+1 A. N... 000000 100644 100644 0000 9b1c src/status.rs
+? notes.txt
+",
+        );
+
+        assert_eq!(
+            status,
+            GitStatus {
+                branch: "main".to_string(),
+                dirty: 3,
+                ahead: 2,
+                behind: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn should_use_commit_when_detached() {
+        let status = GitStatus::parse(
+            "# branch.oid 0e337c3a9d0c1f6b2b8e4a2f3c1d5e6f7a8b9c0d
+# branch.head (detached)
+",
+        );
+
+        assert_eq!(status.branch, "0e337c3");
+        assert_eq!(status.dirty, 0);
+    }
+
+    #[test]
+    #[allow(clippy::literal_string_with_formatting_args)] // Template placeholders.
+    fn should_render_template() {
+        let segment = StatusSegment {
+            project: "axolotl_git".to_string(),
+            git: Some(GitStatus {
+                branch: "fix/#12".to_string(),
+                dirty: 3,
+                ahead: 0,
+                behind: 1,
+            }),
+            activity: 2,
+        };
+
+        assert_eq!(
+            segment.render("{mint}{project}{reset} {branch}{dirty}{ahead}{behind}{activity}"),
+            "#[fg=#b4f8c8]axolotl_git#[default] fix/##12 *3 ↓1 !2"
+        );
+        assert_eq!(
+            StatusSegment::default().render("{project}{branch}{dirty}"),
+            ""
+        );
+    }
+}
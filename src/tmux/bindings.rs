@@ -0,0 +1,253 @@
+use std::{env, process::Command};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{version::TmuxFeature, TmuxCmd};
+use crate::{config::config_file::TmuxConfig, version::Version};
+
+/// Commands that get a binding from `axl tmux init`: the name used in `tmux.bindings`, the
+/// arguments passed to `axl`, the default key in the prefix table, and whether the command needs
+/// a terminal or can run in the background.
+///
+/// `run` does not wait for the task, which would keep the popup open until it finishes. Commands
+/// that only print (`history`, `session list`) or need arguments (`exec`) are left out.
+const BINDABLE_COMMANDS: [(&str, &str, &str, bool); 7] = [
+    ("project-menu", "project-menu", "P", true),
+    ("jump", "jump", "J", true),
+    ("scratch", "scratch", "S", true),
+    ("session-kill", "session kill", "K", true),
+    ("run", "run --detach", "R", true),
+    ("last", "last", "L", false),
+    ("popup", "popup", "T", false),
+];
+
+/// How a binding runs its command.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingRunner {
+    Popup,
+    /// A new window, for commands that need a terminal on servers without popups.
+    Window,
+    Background,
+}
+
+impl BindingRunner {
+    const fn command(self) -> &'static str {
+        match self {
+            Self::Popup => "display-popup",
+            Self::Window => "new-window",
+            Self::Background => "run-shell",
+        }
+    }
+}
+
+/// A prefix table binding that runs an `axl` command, in a popup when it needs a terminal.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    pub name: String,
+    pub key: String,
+    /// Everything after the `axl` executable.
+    pub args: String,
+    pub width: String,
+    pub height: String,
+    pub runner: BindingRunner,
+    /// Whether the binding is described with `bind-key -N`, which needs tmux 3.1.
+    pub note: bool,
+}
+
+/// Whether the running server has a binding loaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BindingStatus {
+    pub binding: Binding,
+    pub loaded: bool,
+    /// What the key is bound to right now, if anything.
+    pub current: Option<String>,
+}
+
+impl Binding {
+    /// The `bind-key` line for a tmux.conf, running the `axl` at `executable`.
+    pub fn to_conf(&self, executable: &str) -> String {
+        let shell_command = format!("'{}' {}", executable.replace('\'', r"'\''"), self.args)
+            .replace('\\', r"\\")
+            .replace('"', "\\\"")
+            .replace('$', r"\$");
+        let runner = match self.runner {
+            BindingRunner::Popup => {
+                format!("display-popup -E -w {} -h {}", self.width, self.height)
+            }
+            BindingRunner::Window => format!("new-window -n axl-{}", self.name),
+            BindingRunner::Background => "run-shell -b".to_string(),
+        };
+        let note = if self.note {
+            format!("-N \"axl {}\" ", self.name)
+        } else {
+            String::new()
+        };
+        format!("bind-key {note}{} {runner} \"{shell_command}\"", self.key)
+    }
+
+    /// `current` is a line from `list-keys`.
+    fn is_loaded(&self, current: &str) -> bool {
+        current.contains(self.runner.command()) && current.contains(&self.args)
+    }
+}
+
+impl TmuxCmd {
+    /// Bindings for every bindable command, with keys and sizes from `config`, for tmux
+    /// `version`. Commands that need a terminal open in a new window when it has no popups.
+    pub fn bindings(config: &TmuxConfig, version: Option<Version>) -> Vec<Binding> {
+        let note = TmuxFeature::KeyNotes.supported_by(version);
+        let terminal_runner = if TmuxFeature::Popup.supported_by(version) {
+            BindingRunner::Popup
+        } else {
+            BindingRunner::Window
+        };
+        BINDABLE_COMMANDS
+            .iter()
+            .filter_map(|(name, command, default_key, needs_terminal)| {
+                let overrides = config.bindings.get(*name).cloned().unwrap_or_default();
+                let key = overrides.key.unwrap_or_else(|| default_key.to_string());
+                (!key.is_empty()).then(|| Binding {
+                    name: name.to_string(),
+                    key,
+                    args: format!("--decoration none --on-error pause {command}"),
+                    width: overrides
+                        .width
+                        .unwrap_or_else(|| config.popup.width.clone()),
+                    height: overrides
+                        .height
+                        .unwrap_or_else(|| config.popup.height.clone()),
+                    runner: if *needs_terminal {
+                        terminal_runner
+                    } else {
+                        BindingRunner::Background
+                    },
+                    note,
+                })
+            })
+            .collect()
+    }
+
+    /// A tmux.conf block binding every command, pointing at the running `axl` executable.
+    #[instrument(err)]
+    pub fn bindings_conf(config: &TmuxConfig) -> Result<String> {
+        let executable = env::current_exe()?;
+        let lines: Vec<String> = Self::bindings(config, Self::version())
+            .iter()
+            .map(|binding| binding.to_conf(&executable.to_string_lossy()))
+            .collect();
+
+        Ok(format!(
+            "# axl bindings, generated by `axl tmux init`\n{}\n",
+            lines.join("\n")
+        ))
+    }
+
+    /// Compare the bindings with what the running server has in its prefix table.
+    #[instrument(err)]
+    pub fn check_bindings(config: &TmuxConfig) -> Result<Vec<BindingStatus>> {
+        Self::bindings(config, Self::version())
+            .into_iter()
+            .map(|binding| {
+                let output = Self::run(
+                    Command::new(Self::CMD)
+                        .arg("list-keys")
+                        .arg("-T")
+                        .arg("prefix")
+                        .arg(&binding.key),
+                )?;
+                let current = Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+                    .filter(|line| output.status.success() && !line.is_empty());
+
+                Ok(BindingStatus {
+                    loaded: current.as_deref().is_some_and(|c| binding.is_loaded(c)),
+                    binding,
+                    current,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::BindingRunner;
+    use crate::{
+        config::config_file::{BindingConfig, TmuxConfig},
+        tmux::TmuxCmd,
+        version::Version,
+    };
+
+    #[test]
+    fn should_apply_binding_overrides() {
+        let config = TmuxConfig {
+            bindings: BTreeMap::from([
+                (
+                    "jump".to_string(),
+                    BindingConfig {
+                        key: Some("C-j".to_string()),
+                        height: Some("40%".to_string()),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "scratch".to_string(),
+                    BindingConfig {
+                        key: Some(String::new()),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        let bindings = TmuxCmd::bindings(&config, Some(Version::new(3, 3, 0)));
+
+        assert_eq!(
+            bindings.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(),
+            vec![
+                "project-menu",
+                "jump",
+                "session-kill",
+                "run",
+                "last",
+                "popup"
+            ]
+        );
+        assert_eq!(
+            bindings[1].to_conf("/usr/bin/axl"),
+            "bind-key -N \"axl jump\" C-j display-popup -E -w 80% -h 40% \"'/usr/bin/axl' --decoration none --on-error pause jump\""
+        );
+        assert_eq!(
+            bindings[3].to_conf("/usr/bin/axl"),
+            "bind-key -N \"axl run\" R display-popup -E -w 80% -h 80% \"'/usr/bin/axl' --decoration none --on-error pause run --detach\""
+        );
+        assert_eq!(
+            bindings[4].to_conf("/usr/bin/axl"),
+            "bind-key -N \"axl last\" L run-shell -b \"'/usr/bin/axl' --decoration none --on-error pause last\""
+        );
+    }
+
+    #[rstest]
+    #[case(
+        Version::new(3, 1, 0),
+        "bind-key -N \"axl jump\" J new-window -n axl-jump \"'/usr/bin/axl' --decoration none --on-error pause jump\""
+    )]
+    #[case(
+        Version::new(3, 0, 0),
+        "bind-key J new-window -n axl-jump \"'/usr/bin/axl' --decoration none --on-error pause jump\""
+    )]
+    fn should_fall_back_on_servers_without_popups(#[case] version: Version, #[case] conf: &str) {
+        let bindings = TmuxCmd::bindings(&TmuxConfig::default(), Some(version));
+
+        assert_eq!(bindings[1].to_conf("/usr/bin/axl"), conf);
+        assert_eq!(bindings[5].name, "last");
+        assert_eq!(bindings[5].runner, BindingRunner::Background);
+    }
+}
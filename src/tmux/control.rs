@@ -0,0 +1,263 @@
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use anyhow::Result;
+use tracing::{debug, instrument, trace};
+
+use crate::error::Error;
+
+/// A single `tmux -C` client that runs commands over one connection instead of spawning a tmux
+/// process for each of them.
+///
+/// Control mode clients must be attached to a session, so connecting fails when the server has
+/// no sessions yet. While connected, the client counts as attached to that session: connecting
+/// runs the server's `client-attached` hooks, and other processes, including other axl commands
+/// like a running `axl watch`, see the session as attached. [`TmuxCmd::sessions`] only leaves out
+/// the client of the current process.
+///
+/// [`TmuxCmd::sessions`]: super::TmuxCmd::sessions
+#[derive(Debug)]
+pub struct ControlClient {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    parser: ControlParser,
+    /// Id of the session this client is attached to.
+    pub session_id: Option<String>,
+}
+
+/// The output block tmux wrote for one command.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ControlReply {
+    pub success: bool,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ControlEvent {
+    /// Reply to a command sent by this client.
+    Reply(ControlReply),
+    /// The client moved to a session, carrying its `$<n>` id.
+    SessionChanged(String),
+    /// The client was detached, or the server exited.
+    Exit,
+}
+
+/// Line by line parser for control mode output.
+#[derive(Debug, Default)]
+pub struct ControlParser {
+    /// Guard (`<time> <number> <flags>`) of the open `%begin` block and the lines read so far.
+    block: Option<(String, Vec<String>)>,
+}
+
+impl ControlParser {
+    pub fn feed(&mut self, line: &str) -> Option<ControlEvent> {
+        if let Some((guard, lines)) = &mut self.block {
+            let end = line
+                .strip_prefix("%end ")
+                .map(|rest| (true, rest))
+                .or_else(|| line.strip_prefix("%error ").map(|rest| (false, rest)));
+
+            return match end {
+                Some((success, rest)) if rest == guard => {
+                    let from_client = Self::from_client(guard);
+                    let lines = std::mem::take(lines);
+                    self.block = None;
+                    // Blocks without the client flag are replies to commands tmux ran on its
+                    // own, like the initial attach.
+                    from_client.then_some(ControlEvent::Reply(ControlReply { success, lines }))
+                }
+                _ => {
+                    lines.push(line.to_string());
+                    None
+                }
+            };
+        }
+
+        if let Some(guard) = line.strip_prefix("%begin ") {
+            self.block = Some((guard.to_string(), Vec::new()));
+            None
+        } else if let Some(rest) = line.strip_prefix("%session-changed ") {
+            rest.split(' ')
+                .next()
+                .map(|id| ControlEvent::SessionChanged(id.to_string()))
+        } else if line.starts_with("%exit") {
+            Some(ControlEvent::Exit)
+        } else {
+            trace!("ignoring control mode notification: {line}");
+            None
+        }
+    }
+
+    fn from_client(guard: &str) -> bool {
+        guard
+            .rsplit(' ')
+            .next()
+            .and_then(|flags| flags.parse::<u32>().ok())
+            .is_some_and(|flags| flags & 1 == 1)
+    }
+}
+
+impl ControlClient {
+    /// Attach a control mode client to the most recently used session, `server_args` are placed
+    /// before the command (like `-L <name>`).
+    #[instrument(err)]
+    pub fn connect(program: &str, server_args: &[&OsStr]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(server_args)
+            .arg("-C")
+            .arg("attach-session")
+            .arg("-f")
+            .arg("no-output,ignore-size")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            Err(Error::ControlModeDisconnected)?
+        };
+
+        let mut client = Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            parser: ControlParser::default(),
+            session_id: None,
+        };
+
+        // Round trip a no-op so a failed attach is noticed here, not on the first real command.
+        client.execute(&[OsStr::new("refresh-client")])?;
+        debug!("tmux control mode client connected");
+
+        Ok(client)
+    }
+
+    /// Run one tmux command, the arguments are quoted so they reach tmux exactly as given.
+    #[instrument(err)]
+    pub fn execute(&mut self, args: &[&OsStr]) -> Result<ControlReply> {
+        let line = args
+            .iter()
+            .map(|arg| arg.to_str().map(Self::quote))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::ControlModeUnsupported)?
+            .join(" ");
+        if line.contains('\n') {
+            Err(Error::ControlModeUnsupported)?
+        }
+
+        writeln!(self.stdin, "{line}")?;
+        self.stdin.flush()?;
+
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if self.stdout.read_line(&mut buffer)? == 0 {
+                Err(Error::ControlModeDisconnected)?
+            }
+            match self.parser.feed(buffer.trim_end_matches(['\r', '\n'])) {
+                Some(ControlEvent::Reply(reply)) => return Ok(reply),
+                Some(ControlEvent::Exit) => Err(Error::ControlModeDisconnected)?,
+                Some(ControlEvent::SessionChanged(id)) => {
+                    trace!("control mode client moved to session {id}");
+                    self.session_id = Some(id);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Single quote an argument for the tmux command parser, which has no escapes inside single
+    /// quotes, so a `'` closes the quote, is escaped, and opens a new one.
+    fn quote(arg: &str) -> String {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Only runs when the connection is replaced, like after switching servers. The client kept until
+/// axl exits is never dropped, the server detaches it once its stdin closes with the process.
+impl Drop for ControlClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::{ControlClient, ControlEvent, ControlParser, ControlReply};
+
+    fn feed_all(parser: &mut ControlParser, output: &str) -> Vec<ControlEvent> {
+        output
+            .lines()
+            .filter_map(|line| parser.feed(line))
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_reply_blocks() {
+        let mut parser = ControlParser::default();
+
+        let events = feed_all(
+            &mut parser,
+            "%begin 1792203285 263 1
+one: 1 windows (created Sat Oct 17 02:14:45 2026)
+%end 1792203285 263 1
+%begin 1792203285 264 1
+can't find session: nope
+%error 1792203285 264 1
+%begin 1792203285 266 0
+%end 1792203285 266 0
+%session-changed $0 one
+%exit",
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                ControlEvent::Reply(ControlReply {
+                    success: true,
+                    lines: vec!["one: 1 windows (created Sat Oct 17 02:14:45 2026)".to_string()],
+                }),
+                ControlEvent::Reply(ControlReply {
+                    success: false,
+                    lines: vec!["can't find session: nope".to_string()],
+                }),
+                ControlEvent::SessionChanged("$0".to_string()),
+                ControlEvent::Exit,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_lines_that_look_like_other_guards() {
+        let mut parser = ControlParser::default();
+
+        let events = feed_all(
+            &mut parser,
+            "%begin 10 1 1
+%end 9 9 1
+%exit
+%end 10 1 1",
+        );
+
+        assert_eq!(
+            events,
+            vec![ControlEvent::Reply(ControlReply {
+                success: true,
+                lines: vec!["%end 9 9 1".to_string(), "%exit".to_string()],
+            })]
+        );
+    }
+
+    #[test]
+    fn should_quote_arguments() {
+        assert_eq!(ControlClient::quote("#{pane_id}"), "'#{pane_id}'");
+        assert_eq!(ControlClient::quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    fmt::Display,
+    process::Command,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::TmuxCmd;
+use crate::multiplexer::SessionName;
+
+/// Printed after the command with its exit status, as `__AXL_EXEC_<id>:<status>`.
+const EXEC_MARKER: &str = "__AXL_EXEC";
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Lines of history searched for the marker, enough for the command to print a screenful.
+const CAPTURED_LINES: u32 = 500;
+
+/// What happened to a command sent to a session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ExecStatus {
+    /// Typed into the pane, without waiting for it to finish.
+    Sent,
+    Exited(i32),
+    TimedOut,
+    /// The pane could not be found or the keys could not be sent.
+    Failed(String),
+}
+
+impl ExecStatus {
+    pub const fn is_success(&self) -> bool {
+        matches!(self, Self::Sent | Self::Exited(0))
+    }
+}
+
+impl Display for ExecStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sent => write!(f, "sent"),
+            Self::Exited(status) => write!(f, "exit {status}"),
+            Self::TimedOut => write!(f, "timed out"),
+            Self::Failed(reason) => write!(f, "failed: {reason}"),
+        }
+    }
+}
+
+/// A row in the `axl exec` summary.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExecResult {
+    pub session: SessionName,
+    /// Unique id of the form `%<n>`, empty when the target could not be found.
+    pub pane: String,
+    pub status: ExecStatus,
+}
+
+impl TmuxCmd {
+    /// Type `command` into the pane at `target` (a window, or `window.pane`, defaulting to the
+    /// active pane) of each session.
+    ///
+    /// With `wait`, the command is followed by a `printf` of its exit status and each pane is
+    /// watched until it shows up or `timeout` passes, so the panes need a POSIX shell prompt.
+    #[instrument(err)]
+    pub fn exec(
+        sessions: &[SessionName],
+        target: Option<&str>,
+        command: &str,
+        wait: Option<Duration>,
+    ) -> Result<Vec<ExecResult>> {
+        // Unique per run, so an older marker left in a pane's history is never mistaken for this
+        // one.
+        let run_id = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let mut results = Vec::new();
+        for (n, session) in sessions.iter().enumerate() {
+            let marker_id = format!("{run_id}_{n}");
+            let typed = if wait.is_some() {
+                marked_command(command, &marker_id)
+            } else {
+                command.to_string()
+            };
+            let (pane, status) = match Self::send_command(session, target, &typed) {
+                Ok(pane) => (pane, ExecStatus::Sent),
+                Err(err) => (String::new(), ExecStatus::Failed(format!("{err:#}"))),
+            };
+            results.push((
+                marker_id,
+                ExecResult {
+                    session: session.clone(),
+                    pane,
+                    status,
+                },
+            ));
+        }
+
+        if let Some(timeout) = wait {
+            let deadline = Instant::now() + timeout;
+            loop {
+                let mut waiting = false;
+                for (marker_id, result) in results
+                    .iter_mut()
+                    .filter(|(_, r)| r.status == ExecStatus::Sent)
+                {
+                    match Self::captured_exit_status(&result.pane, marker_id) {
+                        Ok(Some(status)) => result.status = ExecStatus::Exited(status),
+                        Ok(None) => waiting = true,
+                        Err(err) => result.status = ExecStatus::Failed(format!("{err:#}")),
+                    }
+                }
+                if !waiting {
+                    break;
+                }
+                if Instant::now() >= deadline {
+                    results
+                        .iter_mut()
+                        .filter(|(_, r)| r.status == ExecStatus::Sent)
+                        .for_each(|(_, r)| r.status = ExecStatus::TimedOut);
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// Send `command` and Enter to the target pane, returning its id.
+    fn send_command(session: &SessionName, target: Option<&str>, command: &str) -> Result<String> {
+        let target = format!("={}:{}", session.as_str(), target.unwrap_or(""));
+        debug!("sending to {target}: {command}");
+
+        // Sent to the target first since `display-message` falls back to the current pane
+        // instead of failing when the target does not exist.
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("send-keys")
+                .arg("-t")
+                .arg(&target)
+                .arg("-l")
+                .arg(command),
+        )?)?;
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("send-keys")
+                .arg("-t")
+                .arg(&target)
+                .arg("Enter"),
+        )?)?;
+
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("display-message")
+                .arg("-p")
+                .arg("-t")
+                .arg(&target)
+                .arg("#{pane_id}"),
+        )?;
+        Self::check(&output)?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn captured_exit_status(pane: &str, marker_id: &str) -> Result<Option<i32>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("capture-pane")
+                .arg("-p")
+                .arg("-J")
+                .arg("-S")
+                .arg(format!("-{CAPTURED_LINES}"))
+                .arg("-t")
+                .arg(pane),
+        )?;
+        Self::check(&output)?;
+        Ok(exit_status(
+            &String::from_utf8_lossy(&output.stdout),
+            marker_id,
+        ))
+    }
+}
+
+/// `command` followed by a `printf` of its exit status.
+///
+/// The command goes in a group on its own line so a trailing `&` or comment can't swallow the
+/// `printf`, and the marker is split into `printf` arguments so the typed line never matches it.
+pub fn marked_command(command: &str, marker_id: &str) -> String {
+    format!("{{ {command}\n}}; printf '\\n%s_%s:%d\\n' {EXEC_MARKER} {marker_id} $?")
+}
+
+/// The exit status printed by [`marked_command`], if it has been yet.
+pub fn exit_status(captured: &str, marker_id: &str) -> Option<i32> {
+    let marker = format!("{EXEC_MARKER}_{marker_id}:");
+    captured
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix(&marker)?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::{exit_status, marked_command};
+
+    #[test]
+    fn should_only_find_printed_marker() {
+        let typed = marked_command("git pull", "42_0");
+
+        assert_eq!(
+            typed,
+            "{ git pull\n}; printf '\\n%s_%s:%d\\n' __AXL_EXEC 42_0 $?"
+        );
+        assert_eq!(
+            exit_status(&format!("$ {typed}\nAlready up to date.\n"), "42_0"),
+            None
+        );
+        assert_eq!(
+            exit_status(
+                &format!("$ {typed}\nfatal: not a git repository\n\n__AXL_EXEC_42_0:128\n$"),
+                "42_0"
+            ),
+            Some(128)
+        );
+        assert_eq!(exit_status("__AXL_EXEC_42_1:0", "42_0"), None);
+    }
+
+    #[rstest]
+    #[case::background("true &", 0)]
+    #[case::comment("false # foo", 1)]
+    fn should_report_status_after_any_command(#[case] command: &str, #[case] expected: i32) {
+        let output = Command::new("sh")
+            .args(["-c", &marked_command(command, "42_0")])
+            .output()
+            .expect("sh should run");
+
+        assert_eq!(
+            exit_status(&String::from_utf8_lossy(&output.stdout), "42_0"),
+            Some(expected)
+        );
+    }
+}
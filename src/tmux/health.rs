@@ -0,0 +1,198 @@
+use std::{fmt::Display, path::PathBuf, process::Command};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+use super::{TmuxCmd, FIELD_SEPARATOR};
+use crate::{error::Error, multiplexer::SessionName};
+
+const PANE_FIELDS: [&str; 10] = [
+    "#{session_name}",
+    "#{session_path}",
+    "#{window_index}",
+    "#{window_name}",
+    "#{pane_index}",
+    "#{pane_id}",
+    "#{pane_current_path}",
+    "#{pane_dead}",
+    "#{pane_dead_status}",
+    "#{pane_start_command}",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneProblem {
+    /// The command exited and the pane was kept by `remain-on-exit`.
+    Dead,
+    /// The directory the pane is in was deleted.
+    MissingPath,
+}
+
+impl Display for PaneProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dead => write!(f, "dead"),
+            Self::MissingPath => write!(f, "missing path"),
+        }
+    }
+}
+
+/// A pane listed by `axl session health`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnhealthyPane {
+    pub session: SessionName,
+    pub session_path: PathBuf,
+    pub window_index: u32,
+    pub window_name: String,
+    pub pane_index: u32,
+    /// Unique id of the form `%<n>`.
+    pub id: String,
+    /// Empty for a dead pane, tmux no longer knows it.
+    pub path: PathBuf,
+    pub problem: PaneProblem,
+    /// Exit status of a dead pane, `None` when it is alive or tmux did not get one.
+    pub dead_status: Option<i32>,
+    /// What the pane was started with and is respawned with, empty for the default shell.
+    pub command: String,
+}
+
+impl UnhealthyPane {
+    /// Dead panes and panes whose directory is gone in `list-panes -a` output with
+    /// [`PANE_FIELDS`], outside of scratchpads.
+    pub fn parse(list_panes: &str) -> Result<Vec<Self>> {
+        let mut unhealthy = Vec::new();
+        for line in list_panes.lines().filter(|line| !line.is_empty()) {
+            let unexpected = || Error::UnexpectedTmuxOutput(line.to_string());
+            let fields: Vec<&str> = line.split(FIELD_SEPARATOR).collect();
+            let [session, session_path, window_index, window_name, pane_index, id, path, dead, dead_status, command] =
+                fields[..]
+            else {
+                Err(unexpected())?
+            };
+            let session = SessionName::from_encoded(session);
+            if session.is_scratchpad() {
+                continue;
+            }
+
+            // Linux reads the directory of a process as `<path> (deleted)` once it is removed.
+            let path = PathBuf::from(path.strip_suffix(" (deleted)").unwrap_or(path));
+            let problem = if dead == "1" {
+                PaneProblem::Dead
+            } else if path.as_os_str().is_empty() {
+                // tmux could not read the directory, like that of a root process started with
+                // sudo, which says nothing about whether it still exists.
+                continue;
+            } else if !path.exists() {
+                PaneProblem::MissingPath
+            } else {
+                continue;
+            };
+
+            unhealthy.push(Self {
+                session,
+                session_path: PathBuf::from(session_path),
+                window_index: window_index.parse().map_err(|_| unexpected())?,
+                window_name: window_name.to_string(),
+                pane_index: pane_index.parse().map_err(|_| unexpected())?,
+                id: id.to_string(),
+                path,
+                problem,
+                dead_status: dead_status.parse().ok(),
+                command: command.to_string(),
+            });
+        }
+        Ok(unhealthy)
+    }
+}
+
+/// One line of the respawn prompt.
+impl Display for UnhealthyPane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}.{}  {}  {}",
+            self.session, self.window_index, self.pane_index, self.window_name, self.problem,
+        )?;
+        match (self.problem, self.dead_status) {
+            (PaneProblem::Dead, Some(status)) => write!(f, " (status {status})"),
+            (PaneProblem::MissingPath, _) => write!(f, " ({})", self.path.to_string_lossy()),
+            (PaneProblem::Dead, None) => Ok(()),
+        }
+    }
+}
+
+impl TmuxCmd {
+    /// Dead panes and panes whose directory is gone, in every session on the server.
+    #[instrument(err)]
+    pub fn unhealthy_panes() -> Result<Vec<UnhealthyPane>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-a")
+                .arg("-F")
+                .arg(Self::format(&PANE_FIELDS)),
+        )?;
+        Self::check(&output)?;
+
+        UnhealthyPane::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Restart the pane with the command it was started with. A pane whose directory is gone is
+    /// still running, so its process is killed and it starts again in the session's path.
+    #[instrument(err)]
+    pub fn respawn_pane(pane: &UnhealthyPane) -> Result<()> {
+        info!("respawning {} ({})", pane.id, pane.problem);
+        let mut command = Command::new(Self::CMD);
+        command.arg("respawn-pane").arg("-t").arg(&pane.id);
+        if pane.problem == PaneProblem::MissingPath {
+            command.arg("-k");
+            // tmux falls back to the home directory when this is gone as well.
+            command.arg("-c").arg(&pane.session_path);
+        }
+        Self::check(&Self::run(&mut command)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use similar_asserts::assert_eq;
+
+    use super::{PaneProblem, UnhealthyPane};
+    use crate::multiplexer::SessionName;
+
+    #[test]
+    fn should_find_dead_panes_and_missing_paths() {
+        let list_panes = "api|||/code/api|||1|||editor|||0|||%1|||/|||0||||||
+api|||/code/api|||2|||server|||0|||%2||||||1|||1|||npm run dev
+api|||/code/api|||2|||server|||1|||%3|||/code/api/deleted (deleted)|||0||||||
+docs|||/code/docs|||1|||shell|||0|||%4||||||1||||||
+docs|||/code/docs|||2|||root|||0|||%5||||||0||||||
+docs%__scratch|||/code/docs|||1|||zsh|||0|||%6||||||1||||||
+";
+
+        let unhealthy = UnhealthyPane::parse(list_panes).expect("valid panes should parse");
+
+        assert_eq!(
+            unhealthy
+                .iter()
+                .map(|p| (p.id.as_str(), p.problem, p.dead_status))
+                .collect::<Vec<_>>(),
+            vec![
+                ("%2", PaneProblem::Dead, Some(1)),
+                ("%3", PaneProblem::MissingPath, None),
+                ("%4", PaneProblem::Dead, None),
+            ]
+        );
+        assert_eq!(unhealthy[0].command, "npm run dev");
+        assert_eq!(unhealthy[1].session, SessionName::new("api"));
+        assert_eq!(unhealthy[1].path, PathBuf::from("/code/api/deleted"));
+        assert_eq!(unhealthy[0].to_string(), "api:2.0  server  dead (status 1)");
+    }
+
+    #[test]
+    fn should_reject_unexpected_pane_format() {
+        assert!(UnhealthyPane::parse("api|||/code/api|||1").is_err());
+    }
+}
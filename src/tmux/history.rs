@@ -0,0 +1,49 @@
+use std::{env, process::Command};
+
+use anyhow::Result;
+use tracing::instrument;
+
+use super::{version::TmuxFeature, TmuxCmd};
+
+/// Numbered so it does not replace the user's own hook, or the autosave one.
+const HISTORY_HOOK: &str = "client-session-changed[4201]";
+
+impl TmuxCmd {
+    /// Install (or remove) a global hook that records every session switch in the history, so
+    /// switches made without `axl` are known too.
+    #[allow(clippy::literal_string_with_formatting_args)] // tmux formats, not format args.
+    #[instrument(err)]
+    pub fn set_history_tracking(enabled: bool) -> Result<()> {
+        let mut command = Command::new(Self::CMD);
+        if enabled {
+            // Names are quoted by tmux where it can, axl's own names never contain a quote.
+            let name = if Self::supports(TmuxFeature::QuoteModifier) {
+                "#{q:session_name}"
+            } else {
+                "'#{session_name}'"
+            };
+            let record = format!(
+                "run-shell -b \"'{}' --decoration none session visited {name}\"",
+                env::current_exe()?.to_string_lossy()
+            );
+            // The control mode client switches sessions as well, those are not the user's. Older
+            // servers can not tell it apart, so its switches are recorded too.
+            let hook = if Self::supports(TmuxFeature::ControlModeFormat) {
+                format!(
+                    "if-shell -F '#{{client_control_mode}}' '' '{}'",
+                    record.replace('\'', r"'\''")
+                )
+            } else {
+                record
+            };
+            command
+                .arg("set-hook")
+                .arg("-g")
+                .arg(HISTORY_HOOK)
+                .arg(hook);
+        } else {
+            command.arg("set-hook").arg("-gu").arg(HISTORY_HOOK);
+        }
+        Self::check(&Self::run(&mut command)?)
+    }
+}
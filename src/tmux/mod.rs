@@ -0,0 +1,878 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    ffi::OsStr,
+    fmt::Display,
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Output},
+    sync::Mutex,
+};
+use tracing::{debug, error, info, instrument, trace, warn};
+
+use crate::{
+    config::{
+        config_env::ConfigEnvKey,
+        config_file::{AxlConfig, TmuxConfig},
+        layout::{resolve_path, PaneLayout, SessionLayout, WindowLayout},
+    },
+    error::Error,
+    helper::wrap_command,
+    history::SessionHistory,
+    hooks::{run_hooks, Hook},
+    multiplexer::{Multiplexer, SessionName},
+};
+
+use control::{ControlClient, ControlReply};
+use version::TmuxFeature;
+
+pub mod bindings;
+pub mod control;
+pub mod exec;
+pub mod health;
+pub mod history;
+pub mod pane;
+pub mod popup;
+pub mod prune;
+pub mod record;
+pub mod session;
+pub mod share;
+pub mod snapshot;
+pub mod task;
+pub mod version;
+pub mod watch;
+
+/// Separates fields in `-F` formats, tmux replaces tabs and other control characters with `_`.
+const FIELD_SEPARATOR: &str = "|||";
+
+/// Commands that act on the client that runs them, which would be the control mode client
+/// instead of the user's terminal, so they are run in their own process. `display-message` only
+/// counts when it has no `-t` pane or `-c` client to act on.
+///
+/// The control mode client is disconnected first, otherwise tmux can still pick it as the
+/// current client since it is attached to a session and was the last one active.
+const CLIENT_COMMANDS: [&str; 5] = [
+    "attach-session",
+    "switch-client",
+    "detach-client",
+    "display-message",
+    "display-popup",
+];
+
+/// A tmux server socket, given to tmux as `-S <path>` when it contains a `/` and as `-L <name>`
+/// otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct TmuxSocket(pub String);
+
+impl TmuxSocket {
+    fn args(&self) -> [&OsStr; 2] {
+        let flag = if self.0.contains('/') { "-S" } else { "-L" };
+        [OsStr::new(flag), OsStr::new(&self.0)]
+    }
+}
+
+impl Display for TmuxSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The server every command is sent to, and the control mode connection to it.
+#[derive(Debug)]
+struct TmuxServer {
+    /// `None` is the default server, or the one in `$TMUX` when inside tmux.
+    socket: Option<TmuxSocket>,
+    connection: ControlConnection,
+}
+
+#[derive(Debug)]
+enum ControlConnection {
+    /// Every command spawns its own tmux process.
+    Disabled,
+    /// Connect on the next command.
+    Idle,
+    Connected(ControlClient),
+    /// Connecting failed, fall back to spawning for the rest of the run.
+    Unavailable,
+}
+
+static SERVER: Mutex<TmuxServer> = Mutex::new(TmuxServer {
+    socket: None,
+    connection: ControlConnection::Disabled,
+});
+
+pub struct TmuxCmd {
+    pub cmd: String,
+    pub args: Vec<String>,
+}
+
+impl TmuxCmd {
+    const CMD: &'static str = "tmux";
+
+    /// Apply the `tmux` section of the config file, must be called before any other command.
+    #[instrument]
+    pub fn configure(config: &TmuxConfig) {
+        if let Ok(mut server) = SERVER.lock() {
+            server.connection = if config.control_mode {
+                ControlConnection::Idle
+            } else {
+                ControlConnection::Disabled
+            };
+        }
+        Self::set_socket(config.socket.clone());
+    }
+
+    /// Send every following command to the server on `socket`.
+    #[instrument]
+    pub fn set_socket(socket: Option<TmuxSocket>) {
+        if let Ok(mut server) = SERVER.lock() {
+            if server.socket != socket {
+                server.socket = socket;
+                // The control mode client is attached to the previous server.
+                if !matches!(server.connection, ControlConnection::Disabled) {
+                    server.connection = ControlConnection::Idle;
+                }
+            }
+        }
+    }
+
+    pub fn socket() -> Option<TmuxSocket> {
+        SERVER.lock().ok().and_then(|server| server.socket.clone())
+    }
+
+    /// Path of the socket commands are sent to, worked out like tmux does so it is known before
+    /// the server is running.
+    #[instrument(err)]
+    pub fn socket_path() -> Result<PathBuf> {
+        match Self::socket() {
+            Some(socket) if socket.0.contains('/') => Ok(PathBuf::from(socket.0)),
+            Some(socket) => Self::named_socket_path(&socket.0),
+            None => env::var("TMUX").map_or_else(
+                |_| Self::named_socket_path("default"),
+                |tmux| Ok(PathBuf::from(tmux.split(',').next().unwrap_or_default())),
+            ),
+        }
+    }
+
+    /// Path of the socket tmux uses for `-L <name>`.
+    #[instrument(err)]
+    pub fn named_socket_path(name: &str) -> Result<PathBuf> {
+        let output = wrap_command(Command::new("id").arg("-u"))?;
+        let uid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let dir = env::var("TMUX_TMPDIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| "/tmp".to_string());
+        Ok(PathBuf::from(dir).join(format!("tmux-{uid}")).join(name))
+    }
+
+    /// Create and open the first free `<prefix><n>` session, returning its name.
+    ///
+    /// An `ephemeral` session is destroyed as soon as its last client detaches.
+    #[instrument(skip(config), err)]
+    pub fn unique_session(
+        prefix: &str,
+        path: Option<&Path>,
+        layout: Option<&SessionLayout>,
+        ephemeral: bool,
+        config: &AxlConfig,
+    ) -> Result<SessionName> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => PathBuf::try_from(ConfigEnvKey::Home)?,
+        };
+        if !path.exists() {
+            Err(Error::PathDoesNotExist(path.to_string_lossy().to_string()))?;
+        }
+
+        let existing = Self::list_sessions()?;
+        let name = (0..)
+            .map(|i| SessionName::new(&format!("{prefix}{i}")))
+            .find(|name| !existing.contains(name))
+            .expect("there should always be an unused session number");
+
+        if !Self::create_new_detached(name.as_str(), &path, layout, &[])?
+            .status
+            .success()
+        {
+            eprintln!("{}", "Session failed to open.".red().bold());
+            Err(Error::CouldNotCreateSession)?;
+        }
+        run_hooks(Hook::OnCreate, &name, Some(&path), config)?;
+
+        if ephemeral {
+            for args in ephemeral_hooks(&name) {
+                Self::check(&Self::run(Command::new(Self::CMD).args(args))?)?;
+            }
+        }
+
+        Self::open_existing(&name, config)?;
+
+        Ok(name)
+    }
+}
+
+impl Multiplexer for TmuxCmd {
+    #[instrument(skip(config), err)]
+    fn open(path: &Path, name: &SessionName, config: &AxlConfig) -> Result<()> {
+        info!(
+            "Attempting to open Tmux session with path: {:?}, name: {:?}!",
+            path, name,
+        );
+
+        if !path.exists() {
+            Err(Error::PathDoesNotExist(path.to_string_lossy().to_string()))?;
+        }
+
+        if let Some(socket) = config
+            .project(&name.to_string(), path)
+            .and_then(|p| p.socket.clone())
+        {
+            Self::set_socket(Some(socket));
+        }
+
+        if Self::has_session(name) {
+            info!("Session '{name}' already exists, opening.");
+        } else {
+            info!("Session '{name}' does not already exist, creating and opening.",);
+
+            let layout = config.layout(&name.to_string(), path)?;
+            let env = config.session_env(&name.to_string(), path)?;
+            if !Self::create_new_detached(name.as_str(), path, layout, &env)?
+                .status
+                .success()
+            {
+                eprintln!("{}", "Session failed to open.".red().bold());
+                Err(Error::CouldNotCreateSession)?;
+            }
+            run_hooks(Hook::OnCreate, name, Some(path), config)?;
+        };
+
+        run_hooks(Hook::OnAttach, name, Some(path), config)?;
+        SessionHistory::record(name);
+        Self::switch(name)
+    }
+
+    #[instrument(skip(config), err)]
+    fn open_existing(name: &SessionName, config: &AxlConfig) -> Result<()> {
+        info!(
+            "Attempting to open existing Tmux session with name: {:?}!",
+            name,
+        );
+
+        run_hooks(
+            Hook::OnAttach,
+            name,
+            Self::session_path(name).as_deref(),
+            config,
+        )?;
+        SessionHistory::record(name);
+        Self::switch(name)
+    }
+
+    #[instrument(err)]
+    fn switch(name: &SessionName) -> Result<()> {
+        if !Self::in_session() {
+            trace!("Not currently in session, attempting to attach to tmux session",);
+            Self::attach(name.as_str())?;
+        } else {
+            Self::switch_client(name.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument]
+    fn list_sessions() -> Result<Vec<SessionName>> {
+        Ok(Self::sessions()?.into_iter().map(|s| s.name).collect())
+    }
+
+    #[instrument]
+    fn list_session_paths() -> Result<Vec<(SessionName, Option<PathBuf>)>> {
+        Ok(Self::sessions()?
+            .into_iter()
+            .map(|s| (s.name, Some(s.path)))
+            .collect())
+    }
+
+    #[instrument]
+    fn get_current_session() -> SessionName {
+        SessionName::from_encoded(
+            String::from_utf8_lossy(
+                &Self::run(&mut Self::display_current("#S"))
+                    .expect("tmux should be able to show current session")
+                    .stdout,
+            )
+            .trim_end(),
+        )
+    }
+
+    #[instrument(skip(config), err)]
+    fn kill_sessions(
+        sessions: &[SessionName],
+        current_session: &SessionName,
+        config: &AxlConfig,
+    ) -> Result<Vec<SessionName>> {
+        let mut killed = Vec::new();
+        for session in sessions.iter().filter(|s| *s != current_session) {
+            if Self::kill_with_hooks(session, config)? {
+                killed.push(session.clone());
+            }
+        }
+
+        if sessions.contains(current_session) {
+            debug!("current session [{current_session}] was included to be killed.");
+
+            // Move the client off the session first, killing the session it is attached to would
+            // detach it entirely.
+            if Self::in_session() {
+                let survivor = Self::list_sessions()?
+                    .into_iter()
+                    .find(|s| !sessions.contains(s));
+                if let Some(survivor) = survivor {
+                    debug!("switching to [{survivor}] before killing current session.");
+                    Self::switch_client(survivor.as_str())?;
+                }
+            }
+
+            if Self::kill_with_hooks(current_session, config)? {
+                killed.push(current_session.clone());
+            }
+        }
+
+        Ok(killed)
+    }
+
+    #[instrument]
+    fn has_session(name: &SessionName) -> bool {
+        Self::run(
+            Command::new(Self::CMD)
+                .arg("has-session")
+                .arg("-t")
+                .arg(format!("={}", name.as_str())),
+        )
+        .is_ok_and(|o| o.status.success())
+    }
+}
+
+impl TmuxCmd {
+    /// Run the `on_kill` hooks while the session is still there, then kill it. Returns whether it
+    /// was killed, a failure is only logged.
+    #[instrument(skip(config), err)]
+    pub fn kill_with_hooks(name: &SessionName, config: &AxlConfig) -> Result<bool> {
+        run_hooks(
+            Hook::OnKill,
+            name,
+            Self::session_path(name).as_deref(),
+            config,
+        )?;
+
+        // A scratchpad is only reachable from its session, so it goes with it. Killed first
+        // since killing the current session can end this process.
+        let scratchpad = name.scratchpad();
+        if Self::has_session(&scratchpad) {
+            Self::kill_session(scratchpad.as_str())?;
+            info!("Killed {scratchpad}.");
+        }
+
+        if Self::kill_session(name.as_str()).is_ok() {
+            if name.as_str().is_empty() {
+                warn!("No session picked");
+                return Ok(false);
+            }
+            info!("Killed {name}.");
+            Ok(true)
+        } else {
+            error!("Error while killing {name}.");
+            Ok(false)
+        }
+    }
+
+    /// Directory the session was started in.
+    #[instrument]
+    fn session_path(name: &SessionName) -> Option<PathBuf> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("display-message")
+                .arg("-p")
+                .arg("-t")
+                .arg(format!("={}:", name.as_str()))
+                .arg("#{session_path}"),
+        )
+        .ok()
+        .filter(|o| o.status.success())?;
+
+        Some(PathBuf::from(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+        ))
+    }
+
+    #[allow(dead_code)] // This will likely be needed eventually.
+    #[instrument(err)]
+    fn create_new_detached_attach_if_exists(name: &str, path: &Path) -> Result<Output> {
+        Self::run(
+            Command::new(Self::CMD)
+                .arg("new-session")
+                .arg("-Ad")
+                .arg("-s")
+                .arg(name)
+                .arg("-c")
+                .arg(path.to_str().unwrap_or_default()),
+        )
+    }
+
+    /// Creates the session with the windows and panes from `layout`, or a single shell when there
+    /// is no layout.
+    #[instrument(err)]
+    fn create_new_detached(
+        name: &str,
+        path: &Path,
+        layout: Option<&SessionLayout>,
+        env: &[(String, String)],
+    ) -> Result<Output> {
+        let mut windows = layout.into_iter().flat_map(|l| l.windows.iter());
+        let first_window = windows.next();
+
+        let env_flag = Self::supports(TmuxFeature::SessionEnvironment);
+        let mut new_session = Command::new(Self::CMD);
+        new_session.arg("new-session").arg("-d").arg("-s").arg(name);
+        // Variables given with -e go into the session environment, so every later window and
+        // pane gets them too.
+        for (key, value) in env.iter().filter(|_| env_flag) {
+            new_session.arg("-e").arg(format!("{key}={value}"));
+        }
+
+        let output = Self::run(
+            Self::window_args(&mut new_session, path, first_window)
+                .arg("-P")
+                .arg("-F")
+                .arg("#{pane_id}"),
+        )?;
+
+        if !env_flag && !env.is_empty() && output.status.success() {
+            Self::set_environment_after_create(name, &Self::printed_id(&output)?, env)?;
+        }
+
+        if let Some(window) = first_window.filter(|_| output.status.success()) {
+            Self::build_window(&Self::printed_id(&output)?, path, window)?;
+
+            for window in windows {
+                let window_output = Self::run(
+                    Self::window_args(
+                        Command::new(Self::CMD)
+                            .arg("new-window")
+                            .arg("-d")
+                            .arg("-t")
+                            .arg(format!("={name}:")),
+                        path,
+                        Some(window),
+                    )
+                    .arg("-P")
+                    .arg("-F")
+                    .arg("#{pane_id}"),
+                )?;
+                Self::build_window(&Self::printed_id(&window_output)?, path, window)?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Name and start directory for a window's first pane.
+    fn window_args<'a>(
+        command: &'a mut Command,
+        session_path: &Path,
+        window: Option<&WindowLayout>,
+    ) -> &'a mut Command {
+        let window_path = resolve_path(session_path, window.and_then(|w| w.path.as_ref()));
+        let first_pane_path = resolve_path(
+            &window_path,
+            window
+                .and_then(|w| w.panes.first())
+                .and_then(|p| p.path.as_ref()),
+        );
+
+        if let Some(window_name) = window.and_then(|w| w.name.as_ref()) {
+            command.arg("-n").arg(window_name);
+        }
+        command.arg("-c").arg(first_pane_path)
+    }
+
+    #[instrument(err)]
+    fn build_window(first_pane_id: &str, session_path: &Path, window: &WindowLayout) -> Result<()> {
+        let window_path = resolve_path(session_path, window.path.as_ref());
+        let mut panes = window.panes.iter();
+
+        if let Some(pane) = panes.next() {
+            Self::run_pane_command(first_pane_id, pane)?;
+        }
+
+        let mut previous_pane_id = first_pane_id.to_string();
+        for pane in panes {
+            let mut command = Command::new(Self::CMD);
+            command
+                .arg("split-window")
+                .arg("-d")
+                .arg(pane.split.as_flag())
+                .arg("-t")
+                .arg(&previous_pane_id)
+                .arg("-c")
+                .arg(resolve_path(&window_path, pane.path.as_ref()));
+            if let Some(size) = &pane.size {
+                command.arg("-l").arg(size);
+            }
+            previous_pane_id =
+                Self::printed_id(&Self::run(command.arg("-P").arg("-F").arg("#{pane_id}"))?)?;
+            Self::run_pane_command(&previous_pane_id, pane)?;
+        }
+
+        if let Some(layout) = &window.layout {
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("select-layout")
+                    .arg("-t")
+                    .arg(first_pane_id)
+                    .arg(layout),
+            )?)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(err)]
+    fn run_pane_command(pane_id: &str, pane: &PaneLayout) -> Result<()> {
+        if let Some(pane_command) = &pane.command {
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("send-keys")
+                    .arg("-t")
+                    .arg(pane_id)
+                    .arg("-l")
+                    .arg(pane_command),
+            )?)?;
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("send-keys")
+                    .arg("-t")
+                    .arg(pane_id)
+                    .arg("Enter"),
+            )?)?;
+        }
+        Ok(())
+    }
+
+    /// Without `new-session -e` the variables are set on the session once it exists, and the
+    /// first pane is restarted so it picks them up as well.
+    #[instrument(err)]
+    fn set_environment_after_create(
+        name: &str,
+        pane_id: &str,
+        env: &[(String, String)],
+    ) -> Result<()> {
+        for (key, value) in env {
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("set-environment")
+                    .arg("-t")
+                    .arg(format!("={name}"))
+                    .arg(key)
+                    .arg(value),
+            )?)?;
+        }
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("respawn-pane")
+                .arg("-k")
+                .arg("-t")
+                .arg(pane_id),
+        )?)
+    }
+
+    /// The id printed by a command run with `-P -F '#{pane_id}'`.
+    fn printed_id(output: &Output) -> Result<String> {
+        Self::check(output)?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Run a tmux command over the control mode connection when it is enabled and available,
+    /// otherwise in a new tmux process.
+    #[instrument(err)]
+    fn run(command: &mut Command) -> Result<Output> {
+        Self::run_controlled(command).map_or_else(
+            || {
+                let mut with_socket = Command::new(Self::CMD);
+                if let Some(socket) = Self::socket() {
+                    with_socket.args(socket.args());
+                }
+                wrap_command(with_socket.args(command.get_args()))
+            },
+            Ok,
+        )
+    }
+
+    fn run_controlled(command: &Command) -> Option<Output> {
+        let args: Vec<&OsStr> = command.get_args().collect();
+        if is_client_command(&args) {
+            if let Ok(mut server) = SERVER.lock() {
+                if matches!(server.connection, ControlConnection::Connected(_)) {
+                    server.connection = ControlConnection::Idle;
+                }
+            }
+            return None;
+        }
+
+        let mut server = SERVER.lock().ok()?;
+        if matches!(server.connection, ControlConnection::Idle) {
+            let server_args: Vec<&OsStr> =
+                server.socket.iter().flat_map(TmuxSocket::args).collect();
+            server.connection = match ControlClient::connect(Self::CMD, &server_args) {
+                Ok(client) => ControlConnection::Connected(client),
+                Err(err) => {
+                    debug!("control mode is unavailable, spawning tmux for each command: {err}");
+                    ControlConnection::Unavailable
+                }
+            };
+        }
+
+        let ControlConnection::Connected(client) = &mut server.connection else {
+            return None;
+        };
+        match client.execute(&args) {
+            Ok(reply) => {
+                trace!("{}", reply.lines.join("\n"));
+                Some(reply.into())
+            }
+            Err(err) => {
+                debug!("falling back to spawning tmux: {err}");
+                if matches!(err.downcast_ref(), Some(Error::ControlModeDisconnected)) {
+                    server.connection = ControlConnection::Idle;
+                }
+                None
+            }
+        }
+    }
+
+    /// Id of the session the control mode client is attached to, if it is connected.
+    fn control_session_id() -> Option<String> {
+        match &SERVER.lock().ok()?.connection {
+            ControlConnection::Connected(client) => client.session_id.clone(),
+            _ => None,
+        }
+    }
+
+    /// Build a `-F` format that prints each of `fields` separated by [`FIELD_SEPARATOR`].
+    fn format(fields: &[&str]) -> String {
+        fields.join(FIELD_SEPARATOR)
+    }
+
+    fn check(output: &Output) -> Result<()> {
+        if !output.status.success() {
+            Err(Error::TmuxCommandFailed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))?;
+        }
+        Ok(())
+    }
+
+    #[instrument(err)]
+    fn switch_client(to_name: &str) -> Result<Output> {
+        if let Some(attach) = Self::cross_server_attach(to_name)? {
+            // A client can not switch to a session on another server, so it is replaced by a
+            // client attached to that server instead. Sent to the server from `$TMUX`.
+            return wrap_command(
+                Command::new(Self::CMD)
+                    .arg("detach-client")
+                    .arg("-E")
+                    .arg(attach),
+            );
+        }
+
+        Self::run(
+            Command::new(Self::CMD)
+                .arg("switch-client")
+                .arg("-t")
+                .arg(to_name),
+        )
+    }
+
+    /// The shell command that attaches to `name` when it lives on another server than the
+    /// client we are running in.
+    #[instrument(err)]
+    fn cross_server_attach(name: &str) -> Result<Option<String>> {
+        let (Some(_), Ok(tmux)) = (Self::socket(), env::var("TMUX")) else {
+            return Ok(None);
+        };
+        let current_socket_path = tmux.split(',').next().unwrap_or_default();
+
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("display-message")
+                .arg("-p")
+                .arg("-t")
+                .arg(format!("={name}:"))
+                .arg("#{socket_path}"),
+        )?;
+        Self::check(&output)?;
+        let target_socket_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        if Path::new(current_socket_path) == Path::new(&target_socket_path) {
+            return Ok(None);
+        }
+
+        Ok(Some(format!(
+            "exec {} -S '{}' attach-session -t '={}'",
+            Self::CMD,
+            target_socket_path.replace('\'', r"'\''"),
+            name.replace('\'', r"'\''"),
+        )))
+    }
+
+    #[instrument(err)]
+    fn attach(name: &str) -> Result<Output> {
+        Self::run(
+            Command::new(Self::CMD)
+                .arg("attach-session")
+                .arg("-t")
+                .arg(format!("={name}")),
+        )
+    }
+
+    #[instrument(err)]
+    fn kill_session(project_name: &str) -> Result<()> {
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("kill-session")
+                .arg("-t")
+                .arg(format!("={project_name}")),
+        )?)
+    }
+
+    #[instrument]
+    pub fn in_session() -> bool {
+        env::var("TMUX").is_ok()
+    }
+
+    /// A `display-message -p` of `format` for the pane axl runs in, or for the current client
+    /// outside of tmux.
+    fn display_current(format: &str) -> Command {
+        let mut command = Command::new(Self::CMD);
+        command.arg("display-message").arg("-p");
+        if let Ok(pane) = env::var("TMUX_PANE") {
+            command.arg("-t").arg(pane);
+        }
+        command.arg(format);
+        command
+    }
+}
+
+impl From<ControlReply> for Output {
+    fn from(reply: ControlReply) -> Self {
+        let mut text = reply.lines.join("\n").into_bytes();
+        if !text.is_empty() {
+            text.push(b'\n');
+        }
+
+        if reply.success {
+            Self {
+                status: ExitStatus::from_raw(0),
+                stdout: text,
+                stderr: Vec::new(),
+            }
+        } else {
+            Self {
+                // Raw wait status, exit code 1.
+                status: ExitStatus::from_raw(1 << 8),
+                stdout: Vec::new(),
+                stderr: text,
+            }
+        }
+    }
+}
+
+/// Whether `args` is one of the [`CLIENT_COMMANDS`] that has to run in its own process.
+fn is_client_command(args: &[&OsStr]) -> bool {
+    match args.first().and_then(|a| a.to_str()) {
+        Some("display-message") => !args[1..].iter().any(|a| *a == "-t" || *a == "-c"),
+        Some(command) => CLIENT_COMMANDS.contains(&command),
+        None => true,
+    }
+}
+
+/// `set-hook` arguments that turn on `destroy-unattached` for `name` once a client is in it.
+///
+/// Setting it right away would destroy the session before anything attaches, so it is left to
+/// the first client, which attaches from outside tmux or is switched to it from inside.
+fn ephemeral_hooks(name: &SessionName) -> Vec<Vec<String>> {
+    ["client-attached", "client-session-changed"]
+        .into_iter()
+        .map(|hook| {
+            vec![
+                "set-hook".to_string(),
+                "-t".to_string(),
+                format!("={}:", name.as_str()),
+                hook.to_string(),
+                "set-option destroy-unattached on".to_string(),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use std::ffi::OsStr;
+
+    use super::{ephemeral_hooks, is_client_command};
+    use crate::multiplexer::SessionName;
+
+    #[test]
+    fn should_keep_targeted_messages_on_the_control_connection() {
+        let client_command =
+            |args: &[&str]| is_client_command(&args.iter().map(OsStr::new).collect::<Vec<_>>());
+
+        assert!(!client_command(&[
+            "display-message",
+            "-p",
+            "-t",
+            "%3",
+            "#{pane_current_path}"
+        ]));
+        assert!(!client_command(&[
+            "display-message",
+            "-c",
+            "/dev/pts/1",
+            "done"
+        ]));
+        assert!(!client_command(&["list-sessions"]));
+        assert!(client_command(&["display-message", "-p", "#S"]));
+        assert!(client_command(&["switch-client", "-t", "=api"]));
+        assert!(client_command(&[]));
+    }
+
+    #[test]
+    fn should_destroy_ephemeral_sessions_switched_to_from_inside_tmux() {
+        let hooks = ephemeral_hooks(&SessionName::new("scratch0"));
+
+        assert_eq!(
+            hooks
+                .iter()
+                .map(|args| args[3].as_str())
+                .collect::<Vec<_>>(),
+            vec!["client-attached", "client-session-changed"]
+        );
+        assert_eq!(
+            hooks[1],
+            vec![
+                "set-hook",
+                "-t",
+                "=scratch0:",
+                "client-session-changed",
+                "set-option destroy-unattached on"
+            ]
+        );
+    }
+}
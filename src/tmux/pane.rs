@@ -0,0 +1,146 @@
+use std::{fmt::Display, path::PathBuf, process::Command, str::FromStr};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{TmuxCmd, FIELD_SEPARATOR};
+use crate::{
+    config::config_file::AxlConfig,
+    error::Error,
+    multiplexer::{Multiplexer, SessionName},
+};
+
+const PANE_FIELDS: [&str; 7] = [
+    "#{session_name}",
+    "#{window_index}",
+    "#{window_name}",
+    "#{pane_index}",
+    "#{pane_id}",
+    "#{pane_current_path}",
+    "#{pane_current_command}",
+];
+
+/// A pane as reported by `tmux list-panes -a -F`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TmuxPane {
+    pub session: SessionName,
+    pub window_index: u32,
+    pub window_name: String,
+    pub pane_index: u32,
+    /// Unique id of the form `%<n>`.
+    pub id: String,
+    pub path: PathBuf,
+    /// Name of the program in the foreground.
+    pub command: String,
+}
+
+impl FromStr for TmuxPane {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let unexpected = || Error::UnexpectedTmuxOutput(line.to_string());
+        let fields: Vec<&str> = line.split(FIELD_SEPARATOR).collect();
+        let [session, window_index, window_name, pane_index, id, path, command] = fields[..] else {
+            return Err(unexpected());
+        };
+
+        Ok(Self {
+            session: SessionName::from_encoded(session),
+            window_index: window_index.parse().map_err(|_| unexpected())?,
+            window_name: window_name.to_string(),
+            pane_index: pane_index.parse().map_err(|_| unexpected())?,
+            id: id.to_string(),
+            path: PathBuf::from(path),
+            command: command.to_string(),
+        })
+    }
+}
+
+/// One line in the `axl jump` picker.
+impl Display for TmuxPane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}.{}  {}  {}  {}",
+            self.session,
+            self.window_index,
+            self.pane_index,
+            self.window_name,
+            self.path.to_string_lossy(),
+            self.command,
+        )
+    }
+}
+
+impl TmuxCmd {
+    /// Every pane in every session on the server, leaving out the scratchpads.
+    #[instrument(err)]
+    pub fn panes() -> Result<Vec<TmuxPane>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-a")
+                .arg("-F")
+                .arg(Self::format(&PANE_FIELDS)),
+        )?;
+
+        let mut panes = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(TmuxPane::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        panes.retain(|p| !p.session.is_scratchpad());
+
+        Ok(panes)
+    }
+
+    /// Make `pane` the current pane of its session, and open that session.
+    #[instrument(skip(config), err)]
+    pub fn jump_to_pane(pane: &TmuxPane, config: &AxlConfig) -> Result<()> {
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("select-window")
+                .arg("-t")
+                .arg(&pane.id),
+        )?)?;
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("select-pane")
+                .arg("-t")
+                .arg(&pane.id),
+        )?)?;
+
+        Self::open_existing(&pane.session, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use similar_asserts::assert_eq;
+
+    use super::TmuxPane;
+    use crate::multiplexer::SessionName;
+
+    #[test]
+    fn should_parse_pane_format() {
+        let pane = TmuxPane::from_str("api|||2|||server logs|||1|||%7|||/code/api|||tail")
+            .expect("valid pane line should parse");
+
+        assert_eq!(
+            pane,
+            TmuxPane {
+                session: SessionName::new("api"),
+                window_index: 2,
+                window_name: "server logs".to_string(),
+                pane_index: 1,
+                id: "%7".to_string(),
+                path: PathBuf::from("/code/api"),
+                command: "tail".to_string(),
+            }
+        );
+        assert_eq!(pane.to_string(), "api:2.1  server logs  /code/api  tail");
+    }
+}
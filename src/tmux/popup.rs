@@ -0,0 +1,97 @@
+use std::process::Command;
+
+use anyhow::Result;
+use tracing::{info, instrument};
+
+use super::{version::TmuxFeature, TmuxCmd};
+use crate::{
+    config::config_file::AxlConfig,
+    error::Error,
+    helper::shell_quote,
+    multiplexer::{Multiplexer, SessionName},
+};
+
+impl TmuxCmd {
+    /// Open the current session's scratchpad in a popup, or close the popup when run from inside
+    /// it.
+    ///
+    /// The scratchpad is a hidden session created from the session's path the first time, so its
+    /// shell and history are still there the next time the popup opens.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    #[instrument(skip(config), err)]
+    pub fn toggle_popup(config: &AxlConfig) -> Result<()> {
+        if !Self::in_session() {
+            Err(Error::NotInSession("popup".to_string()))?;
+        }
+        Self::require(TmuxFeature::Popup)?;
+
+        let current = Self::get_current_session();
+        if current.is_scratchpad() {
+            // The popup closes once the client attached inside it is gone.
+            return Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("detach-client")
+                    .arg("-s")
+                    .arg(format!("={}", current.as_str())),
+            )?);
+        }
+
+        let scratchpad = current.scratchpad();
+        if !Self::has_session(&scratchpad) {
+            Self::create_scratchpad(&current, &scratchpad, config)?;
+        }
+
+        let output = Self::run(&mut Self::display_current("#{socket_path}"))?;
+        Self::check(&output)?;
+        let socket_path = String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string();
+
+        // The attach would be refused as nesting with TMUX still set.
+        let attach = format!(
+            "unset TMUX; exec {} -S {} attach-session -t {}",
+            Self::CMD,
+            shell_quote(&socket_path),
+            shell_quote(&format!("={}", scratchpad.as_str())),
+        );
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("display-popup")
+                .arg("-E")
+                .arg("-w")
+                .arg(&config.tmux.popup.width)
+                .arg("-h")
+                .arg(&config.tmux.popup.height)
+                .arg(attach),
+        )?)
+    }
+
+    fn create_scratchpad(
+        session: &SessionName,
+        scratchpad: &SessionName,
+        config: &AxlConfig,
+    ) -> Result<()> {
+        let path = match Self::session_path(session) {
+            Some(path) => path,
+            None => Self::current_path()?,
+        };
+        info!("creating {scratchpad} in {}", path.to_string_lossy());
+
+        let env = config.session_env(&session.to_string(), &path)?;
+        Self::check(&Self::create_new_detached(
+            scratchpad.as_str(),
+            &path,
+            None,
+            &env,
+        )?)?;
+        // The popup has a border already, a status line inside it only takes up space.
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("set-option")
+                .arg("-t")
+                .arg(format!("={}:", scratchpad.as_str()))
+                .arg("status")
+                .arg("off"),
+        )?)
+    }
+}
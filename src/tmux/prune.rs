@@ -0,0 +1,194 @@
+use std::{fmt::Display, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::session::TmuxSession;
+use crate::{
+    helper::{format_duration, glob_match},
+    multiplexer::SessionName,
+};
+
+/// What makes a session a candidate for `axl session prune`, any one is enough.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneCriteria {
+    /// No activity for longer than this.
+    pub idle: Option<Duration>,
+    /// The session path was deleted.
+    pub missing_path: bool,
+    /// No clients attached.
+    pub detached: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    Idle,
+    MissingPath,
+    Detached,
+}
+
+impl Display for PruneReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Idle => write!(f, "idle"),
+            Self::MissingPath => write!(f, "missing path"),
+            Self::Detached => write!(f, "detached"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PruneCandidate {
+    pub session: TmuxSession,
+    /// Seconds since the last activity.
+    pub idle: u64,
+    pub reasons: Vec<PruneReason>,
+}
+
+/// One line of the `axl session prune` table.
+impl Display for PruneCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reasons: Vec<String> = self.reasons.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "{}  idle {}  {}  ({})",
+            self.session.name,
+            format_duration(Duration::from_secs(self.idle)),
+            self.session.path.to_string_lossy(),
+            reasons.join(", "),
+        )
+    }
+}
+
+impl PruneCriteria {
+    /// Sessions matching at least one criterion, leaving out `current`, scratchpads and any
+    /// session whose name matches a `pinned` glob. `now` is in seconds since the unix epoch.
+    pub fn candidates(
+        &self,
+        sessions: Vec<TmuxSession>,
+        current: Option<&SessionName>,
+        pinned: &[String],
+        now: u64,
+    ) -> Vec<PruneCandidate> {
+        sessions
+            .into_iter()
+            .filter(|s| Some(&s.name) != current)
+            // Killed along with their session instead.
+            .filter(|s| !s.name.is_scratchpad())
+            .filter(|s| !pinned.iter().any(|p| glob_match(p, &s.name.to_string())))
+            .filter_map(|session| {
+                let idle = now.saturating_sub(session.last_activity);
+                let reasons: Vec<PruneReason> = [
+                    (
+                        self.idle.is_some_and(|max| idle > max.as_secs()),
+                        PruneReason::Idle,
+                    ),
+                    (
+                        self.missing_path && !session.path.exists(),
+                        PruneReason::MissingPath,
+                    ),
+                    (
+                        self.detached && session.attached == 0,
+                        PruneReason::Detached,
+                    ),
+                ]
+                .into_iter()
+                .filter_map(|(matched, reason)| matched.then_some(reason))
+                .collect();
+
+                (!reasons.is_empty()).then_some(PruneCandidate {
+                    session,
+                    idle,
+                    reasons,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use similar_asserts::assert_eq;
+
+    use super::{PruneCriteria, PruneReason};
+    use crate::{multiplexer::SessionName, tmux::session::TmuxSession};
+
+    const NOW: u64 = 1_700_000_000;
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn session(name: &str, attached: u32, idle_days: u64, path: &str) -> TmuxSession {
+        TmuxSession {
+            name: SessionName::new(name),
+            id: format!("${name}"),
+            attached,
+            windows: 1,
+            created: NOW - 30 * DAY,
+            last_activity: NOW - idle_days * DAY,
+            path: PathBuf::from(path),
+            socket: None,
+        }
+    }
+
+    #[test]
+    fn should_pick_sessions_matching_any_criterion() {
+        let criteria = PruneCriteria {
+            idle: Some(Duration::from_secs(7 * DAY)),
+            missing_path: true,
+            detached: false,
+        };
+        let sessions = vec![
+            session("current", 0, 20, "/"),
+            session("fresh", 0, 1, "/"),
+            session("old", 1, 10, "/"),
+            session("deleted", 0, 0, "/does/not/exist"),
+            session("notes", 0, 30, "/does/not/exist"),
+            TmuxSession {
+                name: SessionName::new("old").scratchpad(),
+                ..session("old", 0, 10, "/")
+            },
+        ];
+
+        let candidates = criteria.candidates(
+            sessions,
+            Some(&SessionName::new("current")),
+            &["note*".to_string()],
+            NOW,
+        );
+
+        assert_eq!(
+            candidates
+                .iter()
+                .map(|c| (c.session.name.to_string(), c.reasons.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("old".to_string(), vec![PruneReason::Idle]),
+                ("deleted".to_string(), vec![PruneReason::MissingPath]),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_pick_detached_sessions() {
+        let criteria = PruneCriteria {
+            detached: true,
+            ..Default::default()
+        };
+
+        let candidates = criteria.candidates(
+            vec![
+                session("attached", 2, 0, "/"),
+                session("detached", 0, 0, "/"),
+            ],
+            Some(&SessionName::new("current")),
+            &[],
+            NOW,
+        );
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            candidates[0].to_string(),
+            "detached  idle 0s  /  (detached)"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    env,
+    path::PathBuf,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use tracing::{info, instrument};
+
+use super::{version::TmuxFeature, TmuxCmd, FIELD_SEPARATOR};
+use crate::{
+    error::Error,
+    helper::{shell_quote, utc_timestamp},
+    multiplexer::SessionName,
+    record::{recording_file_name, recordings_dir, RecordFormat},
+};
+
+/// Pane option holding the file a pane is being recorded to, so only pipes opened by axl are
+/// reported and closed.
+const RECORDING_OPTION: &str = "@axl-recording";
+
+const RECORD_PANE_FIELDS: [&str; 6] = [
+    "#{pane_id}",
+    "#{pane_pipe}",
+    "#{window_index}",
+    "#{pane_index}",
+    "#{pane_width}",
+    "#{pane_height}",
+];
+
+impl TmuxCmd {
+    /// Pipe the output of every pane in `session` into a new file per pane through
+    /// `axl record sink`. Panes that are already piped somewhere are left alone, and panes
+    /// created later are not recorded.
+    ///
+    /// Returns the files that were started.
+    #[instrument(err)]
+    pub fn start_recording(session: &SessionName, format: RecordFormat) -> Result<Vec<PathBuf>> {
+        Self::require(TmuxFeature::PaneOptions)?;
+        let dir = recordings_dir(session)?;
+        let timestamp = utc_timestamp(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs());
+        let executable = env::current_exe()?;
+
+        let mut started = Vec::new();
+        for line in Self::list_session_panes(session, &RECORD_PANE_FIELDS)? {
+            let unexpected = || Error::UnexpectedTmuxOutput(line.clone());
+            let fields: Vec<&str> = line.split(FIELD_SEPARATOR).collect();
+            let [pane_id, pipe, window_index, pane_index, width, height] = fields[..] else {
+                Err(unexpected())?
+            };
+            if pipe == "1" {
+                info!("{pane_id} is already piped, not recording it");
+                continue;
+            }
+
+            let path = dir.join(recording_file_name(
+                &timestamp,
+                window_index.parse().map_err(|_| unexpected())?,
+                pane_index.parse().map_err(|_| unexpected())?,
+                pane_id,
+                format,
+            ));
+            let sink = format!(
+                "{} --decoration none record sink --format {} --width {width} --height {height} --title {} {}",
+                shell_quote(&executable.to_string_lossy()),
+                format.to_possible_value().map_or_else(String::new, |v| v.get_name().to_string()),
+                shell_quote(&format!("{session} {window_index}.{pane_index}")),
+                shell_quote(&path.to_string_lossy()),
+            );
+
+            // The command is expanded like status-left, with formats and strftime sequences.
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("pipe-pane")
+                    .arg("-t")
+                    .arg(pane_id)
+                    .arg(sink.replace('#', "##").replace('%', "%%")),
+            )?)?;
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("set-option")
+                    .arg("-p")
+                    .arg("-t")
+                    .arg(pane_id)
+                    .arg(RECORDING_OPTION)
+                    .arg(&path),
+            )?)?;
+            info!("recording {pane_id} to {}", path.to_string_lossy());
+            started.push(path);
+        }
+        Ok(started)
+    }
+
+    /// Close the pipes axl opened in `session`, returning how many there were. Pipes opened by
+    /// anything else are left alone.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    #[instrument(err)]
+    pub fn stop_recording(session: &SessionName) -> Result<usize> {
+        let mut stopped = 0;
+        for line in Self::list_session_panes(session, &["#{pane_id}", &recording_format()])? {
+            let Some((pane_id, recording)) = line.split_once(FIELD_SEPARATOR) else {
+                Err(Error::UnexpectedTmuxOutput(line))?
+            };
+            if recording.is_empty() {
+                continue;
+            }
+
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("pipe-pane")
+                    .arg("-t")
+                    .arg(pane_id),
+            )?)?;
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("set-option")
+                    .arg("-pu")
+                    .arg("-t")
+                    .arg(pane_id)
+                    .arg(RECORDING_OPTION),
+            )?)?;
+            stopped += 1;
+        }
+        Ok(stopped)
+    }
+
+    /// Files that panes on the server are being recorded to right now.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    #[instrument(err)]
+    pub fn active_recordings() -> Result<Vec<PathBuf>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-a")
+                .arg("-F")
+                .arg(Self::format(&["#{pane_pipe}", &recording_format()])),
+        )?;
+
+        // The option outlives a pipe that ended on its own, like when the sink was killed.
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+            .filter(|(pipe, recording)| *pipe == "1" && !recording.is_empty())
+            .map(|(_, recording)| PathBuf::from(recording))
+            .collect())
+    }
+
+    fn list_session_panes(session: &SessionName, fields: &[&str]) -> Result<Vec<String>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-s")
+                .arg("-t")
+                .arg(format!("={}:", session.as_str()))
+                .arg("-F")
+                .arg(Self::format(fields)),
+        )?;
+        Self::check(&output)?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect())
+    }
+}
+
+/// The `#{@axl-recording}` format.
+fn recording_format() -> String {
+    format!("#{{{RECORDING_OPTION}}}")
+}
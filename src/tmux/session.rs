@@ -0,0 +1,185 @@
+use std::{path::PathBuf, process::Command, str::FromStr};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{TmuxCmd, TmuxSocket, FIELD_SEPARATOR};
+use crate::{error::Error, multiplexer::SessionName};
+
+const SESSION_FIELDS: [&str; 7] = [
+    "#{session_name}",
+    "#{session_id}",
+    "#{session_attached}",
+    "#{session_windows}",
+    "#{session_created}",
+    "#{session_activity}",
+    "#{session_path}",
+];
+
+/// A session as reported by `tmux list-sessions -F`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TmuxSession {
+    pub name: SessionName,
+    /// Unique id of the form `$<n>`, stable across renames.
+    pub id: String,
+    /// Number of clients attached.
+    pub attached: u32,
+    pub windows: u32,
+    /// Seconds since the unix epoch.
+    pub created: u64,
+    /// Seconds since the unix epoch.
+    pub last_activity: u64,
+    pub path: PathBuf,
+    /// The server the session was listed from, `None` for the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<TmuxSocket>,
+}
+
+impl FromStr for TmuxSession {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let unexpected = || Error::UnexpectedTmuxOutput(line.to_string());
+        let fields: Vec<&str> = line.split(FIELD_SEPARATOR).collect();
+        let [name, id, attached, windows, created, last_activity, path] = fields[..] else {
+            return Err(unexpected());
+        };
+
+        Ok(Self {
+            name: SessionName::from_encoded(name),
+            id: id.to_string(),
+            attached: attached.parse().map_err(|_| unexpected())?,
+            windows: windows.parse().map_err(|_| unexpected())?,
+            created: created.parse().map_err(|_| unexpected())?,
+            last_activity: last_activity.parse().map_err(|_| unexpected())?,
+            path: PathBuf::from(path),
+            socket: None,
+        })
+    }
+}
+
+impl TmuxCmd {
+    /// Every session on the server but the scratchpads, an empty vec when the server is not
+    /// running.
+    #[instrument(err)]
+    pub fn sessions() -> Result<Vec<TmuxSession>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-sessions")
+                .arg("-F")
+                .arg(Self::format(&SESSION_FIELDS)),
+        )?;
+
+        let mut sessions = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(TmuxSession::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        sessions.retain(|s| !s.name.is_scratchpad());
+
+        let socket = Self::socket();
+        sessions
+            .iter_mut()
+            .for_each(|s| s.socket.clone_from(&socket));
+
+        // The control mode client is attached to a session too, but it is not a real client.
+        if let Some(control_session_id) = Self::control_session_id() {
+            sessions
+                .iter_mut()
+                .filter(|s| s.id == control_session_id)
+                .for_each(|s| s.attached = s.attached.saturating_sub(1));
+        }
+
+        Ok(sessions)
+    }
+
+    /// Sessions of every server in `servers`, each labeled with the server it lives on. Servers
+    /// that are not running have no sessions.
+    #[instrument(err)]
+    pub fn sessions_on(servers: &[Option<TmuxSocket>]) -> Result<Vec<TmuxSession>> {
+        let previous = Self::socket();
+
+        let mut sessions = Vec::new();
+        let mut result = Ok(());
+        for socket in servers {
+            Self::set_socket(socket.clone());
+            match Self::sessions() {
+                Ok(found) => sessions.extend(found),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        Self::set_socket(previous);
+        result.map(|()| sessions)
+    }
+
+    /// How many sessions other than `current` have a window with an alert (activity, bell or
+    /// silence, depending on the monitor options).
+    #[instrument(err)]
+    pub fn sessions_with_alerts(current: &SessionName) -> Result<usize> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-sessions")
+                .arg("-F")
+                .arg(Self::format(&["#{session_name}", "#{session_alerts}"])),
+        )?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+            .filter(|(name, alerts)| *name != current.as_str() && !alerts.is_empty())
+            .count())
+    }
+
+    /// The working directory of the active pane, followed through `cd`s unlike the session path.
+    #[instrument(err)]
+    pub fn current_path() -> Result<PathBuf> {
+        let output = Self::run(&mut Self::display_current("#{pane_current_path}"))?;
+        Self::check(&output)?;
+
+        Ok(PathBuf::from(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use similar_asserts::assert_eq;
+
+    use super::TmuxSession;
+    use crate::multiplexer::SessionName;
+
+    #[test]
+    fn should_parse_session_format() {
+        let session = TmuxSession::from_str(
+            "axolotl_git|||$3|||1|||2|||1714000000|||1714000500|||/code/axolotl_git",
+        )
+        .expect("valid session line should parse");
+
+        assert_eq!(
+            session,
+            TmuxSession {
+                name: SessionName::new("axolotl_git"),
+                id: "$3".to_string(),
+                attached: 1,
+                windows: 2,
+                created: 1714000000,
+                last_activity: 1714000500,
+                path: PathBuf::from("/code/axolotl_git"),
+                socket: None,
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_unexpected_output() {
+        assert!(TmuxSession::from_str("axolotl_git: 2 windows (created Mon)").is_err());
+    }
+}
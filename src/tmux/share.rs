@@ -0,0 +1,289 @@
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+use super::{version::TmuxFeature, TmuxCmd, TmuxSocket, FIELD_SEPARATOR};
+use crate::{
+    config::config_file::AxlConfig,
+    error::Error,
+    helper::{shell_quote, wrap_command},
+    hooks::{run_hooks, Hook},
+    multiplexer::{Multiplexer, SessionName},
+};
+
+/// Read and write for the socket's group, which is what lets other users connect to it.
+const GROUP_ACCESS: u32 = 0o060;
+
+/// A session running on its own server so other users can attach to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SharedSession {
+    pub name: SessionName,
+    pub socket: TmuxSocket,
+    /// Shell command that attaches without being able to type.
+    pub attach_read_only: String,
+    pub attach_read_write: String,
+}
+
+impl SharedSession {
+    /// `name` on `socket`, with the commands that attach to it from another account.
+    pub fn new(name: SessionName, socket: TmuxSocket) -> Self {
+        let attach = |flags: &str| {
+            format!(
+                "{} -S {} attach-session{flags} -t {}",
+                TmuxCmd::CMD,
+                shell_quote(&socket.0),
+                shell_quote(&format!("={}", name.as_str())),
+            )
+        };
+        Self {
+            attach_read_only: attach(" -r"),
+            attach_read_write: attach(""),
+            name,
+            socket,
+        }
+    }
+}
+
+impl TmuxCmd {
+    /// The socket a session is shared on, in the temp dir so other users can reach it.
+    pub fn share_socket(name: &SessionName) -> Result<TmuxSocket> {
+        let file_name = format!("axl-share-{}-{}", current_user()?, name.as_str())
+            .replace(std::path::MAIN_SEPARATOR, "_");
+        Ok(TmuxSocket(
+            env::temp_dir()
+                .join(file_name)
+                .to_string_lossy()
+                .to_string(),
+        ))
+    }
+
+    /// Start `name` on its share socket, created from the session's path on the current server
+    /// unless it is already there, and open the socket to `group` (or the user's own group).
+    ///
+    /// From tmux 3.3 only the server owner and users added with `server-access` can attach, so
+    /// `users` are added, read-only unless `write` is set. Running it again for the same users
+    /// changes their access.
+    #[instrument(skip(config), err)]
+    pub fn share(
+        name: &SessionName,
+        users: &[String],
+        group: Option<&str>,
+        write: bool,
+        config: &AxlConfig,
+    ) -> Result<SharedSession> {
+        if !users.is_empty() {
+            Self::require(TmuxFeature::ServerAccess)?;
+        }
+        let socket = Self::share_socket(name)?;
+        let path = Self::session_path(name);
+
+        let previous = Self::socket();
+        Self::set_socket(Some(socket.clone()));
+        let result = Self::share_on_socket(name, path.as_deref(), users, write, config);
+        Self::set_socket(previous);
+        result?;
+
+        let socket_path = Path::new(&socket.0);
+        if let Some(group) = group {
+            let output = wrap_command(Command::new("chgrp").arg(group).arg(socket_path))?;
+            if !output.status.success() {
+                Err(Error::ShareFailed(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ))?;
+            }
+        }
+        let mut permissions = fs::metadata(socket_path)?.permissions();
+        permissions.set_mode(permissions.mode() | GROUP_ACCESS);
+        fs::set_permissions(socket_path, permissions)?;
+
+        Ok(SharedSession::new(name.clone(), socket))
+    }
+
+    /// Take away access from `users`, or from everyone but the owner when there are none. Their
+    /// clients are detached, and when everyone loses access the socket is closed to the group.
+    ///
+    /// Returns the users that lost access. Single users can only be revoked from tmux 3.3, older
+    /// servers do not know who a client belongs to.
+    #[instrument(err)]
+    pub fn revoke_share(name: &SessionName, users: &[String]) -> Result<Vec<String>> {
+        if !users.is_empty() {
+            Self::require(TmuxFeature::ServerAccess)?;
+        }
+        let socket = Self::share_socket(name)?;
+        if !Path::new(&socket.0).exists() {
+            Err(Error::ShareFailed(format!("{name} is not shared")))?;
+        }
+
+        let previous = Self::socket();
+        Self::set_socket(Some(socket.clone()));
+        let result = Self::revoke_on_socket(users);
+        Self::set_socket(previous);
+        let revoked = result?;
+
+        if users.is_empty() {
+            let socket_path = PathBuf::from(&socket.0);
+            let mut permissions = fs::metadata(&socket_path)?.permissions();
+            permissions.set_mode(permissions.mode() & !0o077);
+            fs::set_permissions(&socket_path, permissions)?;
+        }
+
+        Ok(revoked)
+    }
+
+    fn share_on_socket(
+        name: &SessionName,
+        path: Option<&Path>,
+        users: &[String],
+        write: bool,
+        config: &AxlConfig,
+    ) -> Result<()> {
+        if Self::has_session(name) {
+            info!("{name} is already shared");
+        } else {
+            let path = path.ok_or_else(|| Error::SessionNotRunning(name.to_string()))?;
+            let layout = config.layout(&name.to_string(), path)?;
+            let env = config.session_env(&name.to_string(), path)?;
+            Self::check(&Self::create_new_detached(
+                name.as_str(),
+                path,
+                layout,
+                &env,
+            )?)?;
+            run_hooks(Hook::OnCreate, name, Some(path), config)?;
+        }
+
+        let allowed = Self::server_access_users()?;
+        for user in users {
+            if !allowed.contains(user) {
+                Self::check(&Self::run(
+                    Command::new(Self::CMD)
+                        .arg("server-access")
+                        .arg("-a")
+                        .arg(user),
+                )?)?;
+            }
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("server-access")
+                    .arg(if write { "-w" } else { "-r" })
+                    .arg(user),
+            )?)?;
+        }
+        Ok(())
+    }
+
+    fn revoke_on_socket(users: &[String]) -> Result<Vec<String>> {
+        let owner = current_user()?;
+        let revoked = if users.is_empty() {
+            // tmux refuses to change the access of the owner and root.
+            Self::server_access_users()?
+                .into_iter()
+                .filter(|user| *user != owner && user != "root")
+                .collect()
+        } else {
+            users.to_vec()
+        };
+
+        if Self::supports(TmuxFeature::ServerAccess) {
+            for user in &revoked {
+                Self::check(&Self::run(
+                    Command::new(Self::CMD)
+                        .arg("server-access")
+                        .arg("-d")
+                        .arg(user),
+                )?)?;
+            }
+        }
+
+        // Older servers do not know who a client belongs to, their user is empty.
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-clients")
+                .arg("-F")
+                .arg(Self::format(&["#{client_name}", "#{client_user}"])),
+        )?;
+        for (client, user) in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+            .filter(|(_, user)| !user.is_empty() && *user != owner)
+            .filter(|(_, user)| users.is_empty() || users.iter().any(|u| u == user))
+        {
+            info!("detaching {client} of {user}");
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("detach-client")
+                    .arg("-t")
+                    .arg(client),
+            )?)?;
+        }
+
+        Ok(revoked)
+    }
+
+    /// Users added with `server-access`, empty on servers without it.
+    fn server_access_users() -> Result<Vec<String>> {
+        if !Self::supports(TmuxFeature::ServerAccess) {
+            return Ok(Vec::new());
+        }
+        let output = Self::run(Command::new(Self::CMD).arg("server-access").arg("-l"))?;
+        Self::check(&output)?;
+
+        Ok(parse_server_access(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+/// Users in `server-access -l` output, where each is listed as `<user> (R)` or `<user> (W)`.
+fn parse_server_access(list: &str) -> Vec<String> {
+    list.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn current_user() -> Result<String> {
+    let output = wrap_command(Command::new("id").arg("-un"))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::{parse_server_access, SharedSession};
+    use crate::{multiplexer::SessionName, tmux::TmuxSocket};
+
+    #[test]
+    fn should_build_attach_commands() {
+        let shared = SharedSession::new(
+            SessionName::new("api v2"),
+            TmuxSocket("/tmp/axl-share-me-api v2".to_string()),
+        );
+
+        assert_eq!(
+            shared.attach_read_only,
+            "tmux -S '/tmp/axl-share-me-api v2' attach-session -r -t '=api v2'"
+        );
+        assert_eq!(
+            shared.attach_read_write,
+            "tmux -S '/tmp/axl-share-me-api v2' attach-session -t '=api v2'"
+        );
+    }
+
+    #[test]
+    fn should_parse_server_access_list() {
+        assert_eq!(
+            parse_server_access("alice (W)\nbob (R)\n\n"),
+            vec!["alice", "bob"]
+        );
+        assert_eq!(parse_server_access(""), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use super::{TmuxCmd, FIELD_SEPARATOR};
+use crate::{
+    config::{
+        config_file::AxlConfig,
+        layout::{PaneLayout, SessionLayout, WindowLayout},
+    },
+    helper::{state_dir, wrap_command},
+    hooks::{run_hooks, Hook},
+    multiplexer::{Multiplexer, SessionName},
+};
+
+/// Snapshot of the default server, other servers get one named after their socket.
+const SNAPSHOT_FILE: &str = "sessions.json";
+const PANE_FIELDS: [&str; 8] = [
+    "#{session_name}",
+    "#{session_path}",
+    "#{window_index}",
+    "#{window_name}",
+    "#{window_layout}",
+    "#{pane_current_path}",
+    "#{pane_current_command}",
+    "#{pane_pid}",
+];
+
+/// Foreground commands that are just the pane's shell, these are never re-run on restore.
+const SHELLS: [&str; 9] = [
+    "bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "csh", "nu",
+];
+
+/// Hooks that trigger an autosave, numbered so they do not replace the user's own hooks.
+///
+/// Client attach and detach hooks are left out, every `axl` run in control mode attaches and
+/// detaches a client.
+const AUTOSAVE_HOOKS: [&str; 4] = [
+    "session-created[4200]",
+    "session-closed[4200]",
+    "session-renamed[4200]",
+    "client-session-changed[4200]",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Seconds since the unix epoch.
+    pub saved_at: u64,
+    pub sessions: Vec<SessionSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionSnapshot {
+    pub name: String,
+    pub path: PathBuf,
+    pub windows: Vec<WindowSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowSnapshot {
+    pub index: String,
+    pub name: String,
+    /// The `#{window_layout}` string, which restores the exact pane geometry.
+    pub layout: String,
+    pub panes: Vec<PaneSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PaneSnapshot {
+    pub path: PathBuf,
+    /// Full command line of the foreground process, `None` when the pane is sitting at a shell.
+    pub command: Option<String>,
+}
+
+impl Snapshot {
+    /// Parse `list-panes -a` output with [`PANE_FIELDS`], `child_args` maps a pane's shell pid to
+    /// the command line of the process running in it.
+    pub fn parse(list_panes: &str, child_args: &HashMap<String, String>, saved_at: u64) -> Self {
+        let mut sessions: Vec<SessionSnapshot> = Vec::new();
+
+        for fields in list_panes
+            .lines()
+            .map(|line| line.split(FIELD_SEPARATOR).collect::<Vec<_>>())
+            .filter(|fields| fields.len() == PANE_FIELDS.len())
+        {
+            let [session_name, session_path, window_index, window_name, window_layout, pane_path, pane_command, pane_pid] =
+                fields[..]
+            else {
+                continue;
+            };
+            // Popups open these again on demand, restoring one would make it a visible session.
+            if SessionName::from_encoded(session_name).is_scratchpad() {
+                continue;
+            }
+
+            if sessions.last().is_none_or(|s| s.name != session_name) {
+                sessions.push(SessionSnapshot {
+                    name: session_name.to_string(),
+                    path: PathBuf::from(session_path),
+                    windows: Vec::new(),
+                });
+            }
+            let Some(session) = sessions.last_mut() else {
+                continue;
+            };
+
+            if session
+                .windows
+                .last()
+                .is_none_or(|w| w.index != window_index)
+            {
+                session.windows.push(WindowSnapshot {
+                    index: window_index.to_string(),
+                    name: window_name.to_string(),
+                    layout: window_layout.to_string(),
+                    panes: Vec::new(),
+                });
+            }
+            let Some(window) = session.windows.last_mut() else {
+                continue;
+            };
+
+            window.panes.push(PaneSnapshot {
+                path: PathBuf::from(pane_path),
+                command: (!SHELLS.contains(&pane_command)).then(|| {
+                    child_args
+                        .get(pane_pid)
+                        .cloned()
+                        .unwrap_or_else(|| pane_command.to_string())
+                }),
+            });
+        }
+
+        Self { saved_at, sessions }
+    }
+}
+
+impl SessionSnapshot {
+    fn to_layout(&self, with_commands: bool) -> SessionLayout {
+        SessionLayout {
+            windows: self
+                .windows
+                .iter()
+                .map(|window| WindowLayout {
+                    name: Some(window.name.clone()),
+                    path: None,
+                    layout: Some(window.layout.clone()),
+                    panes: window
+                        .panes
+                        .iter()
+                        .map(|pane| PaneLayout {
+                            path: Some(pane.path.clone()),
+                            command: pane.command.clone().filter(|_| with_commands),
+                            ..Default::default()
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TmuxCmd {
+    /// Write every session on the server to `$XDG_STATE_HOME/axl/sessions.json`, or a file named
+    /// after the socket for servers other than the default one.
+    #[instrument(err)]
+    pub fn save_snapshot() -> Result<(PathBuf, Snapshot)> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-a")
+                .arg("-F")
+                .arg(Self::format(&PANE_FIELDS)),
+        )?;
+        Self::check(&output)?;
+
+        let snapshot = Snapshot::parse(
+            &String::from_utf8_lossy(&output.stdout),
+            &Self::child_args()?,
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        );
+
+        let path = Self::snapshot_path()?;
+        fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+        info!(
+            "saved {} sessions to {}",
+            snapshot.sessions.len(),
+            path.to_string_lossy()
+        );
+
+        Ok((path, snapshot))
+    }
+
+    /// Recreate every saved session that is not already running, returning the restored names.
+    /// Sessions of a project get its environment and `on_create` hooks like a newly opened one.
+    #[instrument(skip(config), err)]
+    pub fn restore_snapshot(with_commands: bool, config: &AxlConfig) -> Result<Vec<String>> {
+        let snapshot: Snapshot =
+            serde_json::from_str(&fs::read_to_string(Self::snapshot_path()?)?)?;
+
+        let mut restored = Vec::new();
+        for session in &snapshot.sessions {
+            let name = SessionName::from_encoded(&session.name);
+            if Self::has_session(&name) {
+                info!("session {} is already running, skipping.", session.name);
+                continue;
+            }
+
+            let env = config.session_env(&name.to_string(), &session.path)?;
+            match Self::create_new_detached(
+                &session.name,
+                &session.path,
+                Some(&session.to_layout(with_commands)),
+                &env,
+            ) {
+                Ok(output) if output.status.success() => {
+                    run_hooks(Hook::OnCreate, &name, Some(&session.path), config)?;
+                    restored.push(session.name.clone());
+                }
+                Ok(output) => warn!(
+                    "could not restore {}: {}",
+                    session.name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(err) => warn!("could not restore {}: {err:?}", session.name),
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Install (or remove) global tmux hooks that save a snapshot whenever sessions change.
+    ///
+    /// The hooks save the server they were installed on, whatever socket the config or
+    /// environment points at when they run.
+    #[instrument(err)]
+    pub fn set_autosave(enabled: bool) -> Result<()> {
+        let save_command = format!(
+            "run-shell -b \"'{}' --decoration none --socket '{}' session save --silent\"",
+            env::current_exe()?.to_string_lossy(),
+            Self::socket_path()?.to_string_lossy()
+        );
+
+        for hook in AUTOSAVE_HOOKS {
+            let mut command = Command::new(Self::CMD);
+            if enabled {
+                command
+                    .arg("set-hook")
+                    .arg("-g")
+                    .arg(hook)
+                    .arg(&save_command);
+            } else {
+                command.arg("set-hook").arg("-gu").arg(hook);
+            }
+            Self::check(&Self::run(&mut command)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn snapshot_path() -> Result<PathBuf> {
+        let default = Self::named_socket_path("default")?;
+        Ok(state_dir()?.join(snapshot_file(&Self::socket_path()?, &default)))
+    }
+
+    /// Command lines of processes keyed by their parent pid, used to find what a pane's shell is
+    /// running since tmux only reports the program name.
+    fn child_args() -> Result<HashMap<String, String>> {
+        let output = wrap_command(Command::new("ps").arg("-ao").arg("ppid=,args="))?;
+        let mut child_args = HashMap::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some((ppid, args)) = line.trim_start().split_once(' ') {
+                child_args
+                    .entry(ppid.to_string())
+                    .or_insert_with(|| args.trim().to_string());
+            }
+        }
+        Ok(child_args)
+    }
+}
+
+/// Name of the snapshot file of the server listening on `socket`, `default` being the socket of
+/// the default server.
+fn snapshot_file(socket: &Path, default: &Path) -> String {
+    if socket == default {
+        SNAPSHOT_FILE.to_string()
+    } else {
+        format!(
+            "sessions-{}.json",
+            socket
+                .to_string_lossy()
+                .trim_start_matches(MAIN_SEPARATOR)
+                .replace(MAIN_SEPARATOR, "_")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    };
+
+    use similar_asserts::assert_eq;
+
+    use super::{snapshot_file, PaneSnapshot, Snapshot};
+
+    #[test]
+    fn should_keep_a_snapshot_per_server() {
+        let default = Path::new("/tmp/tmux-1000/default");
+
+        assert_eq!(snapshot_file(default, default), "sessions.json");
+        assert_eq!(
+            snapshot_file(Path::new("/tmp/tmux-1000/work"), default),
+            "sessions-tmp_tmux-1000_work.json"
+        );
+    }
+
+    #[test]
+    fn should_group_panes_into_sessions_and_windows() {
+        let list_panes = "api|||/code/api|||1|||editor|||layout-a|||/code/api|||nvim|||100
+api|||/code/api|||2|||server|||layout-b|||/code/api|||zsh|||200
+api|||/code/api|||2|||server|||layout-b|||/code/api/web|||node|||300
+api%__scratch|||/code/api|||1|||zsh|||layout-d|||/code/api|||zsh|||500
+docs|||/code/docs|||1|||zsh|||layout-c|||/code/docs|||zsh|||400
+";
+        let child_args = HashMap::from([
+            ("100".to_string(), "nvim src/main.rs".to_string()),
+            (
+                "200".to_string(),
+                "ignored because zsh is a shell".to_string(),
+            ),
+        ]);
+
+        let snapshot = Snapshot::parse(list_panes, &child_args, 42);
+
+        assert_eq!(snapshot.saved_at, 42);
+        assert_eq!(snapshot.sessions.len(), 2);
+        assert_eq!(snapshot.sessions[0].windows.len(), 2);
+        assert_eq!(
+            snapshot.sessions[0].windows[1].panes,
+            vec![
+                PaneSnapshot {
+                    path: PathBuf::from("/code/api"),
+                    command: None,
+                },
+                PaneSnapshot {
+                    path: PathBuf::from("/code/api/web"),
+                    command: Some("node".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            snapshot.sessions[0].windows[0].panes[0].command.as_deref(),
+            Some("nvim src/main.rs")
+        );
+        assert_eq!(snapshot.sessions[1].windows[0].layout, "layout-c");
+    }
+}
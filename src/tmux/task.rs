@@ -0,0 +1,240 @@
+use std::{collections::BTreeMap, env, process::Command, thread, time::Duration};
+
+use anyhow::Result;
+use tracing::{debug, info, instrument};
+
+use super::{TmuxCmd, FIELD_SEPARATOR};
+use crate::{
+    config::config_file::AxlConfig,
+    error::Error,
+    helper::shell_quote,
+    multiplexer::{Multiplexer, SessionName},
+};
+
+/// Window option the task's exit status is stored in once it finishes.
+const TASK_STATUS_OPTION: &str = "@axl-task-status";
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Where a task is at, read from its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskProgress {
+    Running,
+    Exited(i32),
+    /// The pane is dead without having stored a status, its shell was killed.
+    Killed,
+}
+
+impl TmuxCmd {
+    /// The session tasks are run in: the named one, the current one inside tmux, and otherwise
+    /// the one started in the current directory.
+    #[instrument(err)]
+    pub fn task_session(name: Option<&SessionName>) -> Result<SessionName> {
+        if let Some(name) = name {
+            return Ok(name.clone());
+        }
+        if Self::in_session() {
+            return Ok(Self::get_current_session());
+        }
+
+        let current_dir = env::current_dir()?;
+        Self::list_session_paths()?
+            .into_iter()
+            .find(|(_, path)| path.as_deref() == Some(current_dir.as_path()))
+            .map(|(name, _)| name)
+            .ok_or_else(|| {
+                Error::NoSessionForPath(current_dir.to_string_lossy().to_string()).into()
+            })
+    }
+
+    /// Tasks of the project the session was started in.
+    #[instrument(skip(config), err)]
+    pub fn session_tasks(
+        session: &SessionName,
+        config: &AxlConfig,
+    ) -> Result<BTreeMap<String, String>> {
+        let path = Self::session_path(session)
+            .ok_or_else(|| Error::SessionNotRunning(session.to_string()))?;
+        config.tasks(&session.to_string(), &path)
+    }
+
+    /// Run `command` in the window named after `task`, respawning it when it is already there,
+    /// and with `wait` block until it finishes and return its exit status. Waiting fails when the
+    /// window is killed first.
+    ///
+    /// The window is kept open after the command exits so its output can still be read.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    #[instrument(err)]
+    pub fn run_task(
+        session: &SessionName,
+        task: &str,
+        command: &str,
+        wait: bool,
+    ) -> Result<Option<i32>> {
+        let path = Self::session_path(session)
+            .ok_or_else(|| Error::SessionNotRunning(session.to_string()))?;
+
+        let window = match Self::task_window(session, task)? {
+            Some(window) => window,
+            None => {
+                info!("creating window {task} in {session}");
+                let output = Self::run(
+                    Command::new(Self::CMD)
+                        .arg("new-window")
+                        .arg("-d")
+                        .arg("-P")
+                        .arg("-F")
+                        .arg("#{window_id}")
+                        .arg("-t")
+                        .arg(format!("={}:", session.as_str()))
+                        .arg("-n")
+                        .arg(task)
+                        .arg("-c")
+                        .arg(&path),
+                )?;
+                Self::printed_id(&output)?
+            }
+        };
+        // Set before the task starts, a quick one would otherwise take its window with it.
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("set-option")
+                .arg("-w")
+                .arg("-t")
+                .arg(&window)
+                .arg("remain-on-exit")
+                .arg("on"),
+        )?)?;
+        // The status of an earlier run in the same window would be taken for this one.
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("set-option")
+                .arg("-wu")
+                .arg("-t")
+                .arg(&window)
+                .arg(TASK_STATUS_OPTION),
+        )?)?;
+
+        let script = task_script(command);
+        debug!("running in {window}: {script}");
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("respawn-window")
+                .arg("-k")
+                .arg("-t")
+                .arg(&window)
+                .arg("-c")
+                .arg(&path)
+                .arg(format!("sh -c {}", shell_quote(&script))),
+        )?)?;
+
+        if !wait {
+            return Ok(None);
+        }
+
+        // Polled rather than signalled, a task whose window is killed would never signal. Unlike
+        // `display-message`, `list-panes` fails once the window is gone.
+        loop {
+            let output = Self::run(
+                Command::new(Self::CMD)
+                    .arg("list-panes")
+                    .arg("-t")
+                    .arg(&window)
+                    .arg("-F")
+                    .arg(Self::format(&[
+                        "#{pane_dead}",
+                        "#{pane_dead_status}",
+                        &format!("#{{{TASK_STATUS_OPTION}}}"),
+                    ])),
+            )?;
+            if !output.status.success() {
+                Err(Error::TaskKilled(task.to_string()))?;
+            }
+            match task_progress(String::from_utf8_lossy(&output.stdout).trim())? {
+                TaskProgress::Running => thread::sleep(POLL_INTERVAL),
+                TaskProgress::Exited(status) => return Ok(Some(status)),
+                TaskProgress::Killed => Err(Error::TaskKilled(task.to_string()))?,
+            }
+        }
+    }
+
+    /// Id of the window named `task` in the session, if there is one.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    fn task_window(session: &SessionName, task: &str) -> Result<Option<String>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-windows")
+                .arg("-t")
+                .arg(format!("={}:", session.as_str()))
+                .arg("-F")
+                .arg(Self::format(&["#{window_id}", "#{window_name}"])),
+        )?;
+        Self::check(&output)?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+            .find(|(_, name)| *name == task)
+            .map(|(id, _)| id.to_string()))
+    }
+}
+
+/// Shell script that runs `command` and stores its exit status on the window.
+///
+/// The command runs in a subshell on lines of its own, so an `exit` or a trailing comment in it
+/// can not skip the rest.
+fn task_script(command: &str) -> String {
+    format!(
+        "(\n{command}\n)\nstatus=$?\n{tmux} set-option -w -t \"$TMUX_PANE\" {TASK_STATUS_OPTION} \"$status\"\nexit \"$status\"",
+        tmux = TmuxCmd::CMD,
+    )
+}
+
+/// Read `#{pane_dead}`, `#{pane_dead_status}` and the task status option of a task's window.
+/// The stored status wins over the pane's, which is the status of `sh` rather than the task's.
+fn task_progress(line: &str) -> Result<TaskProgress> {
+    let unexpected = || Error::UnexpectedTmuxOutput(format!("task progress '{line}'"));
+    let [dead, dead_status, status] = line.split(FIELD_SEPARATOR).collect::<Vec<_>>()[..] else {
+        Err(unexpected())?
+    };
+
+    Ok(if !status.is_empty() {
+        TaskProgress::Exited(status.parse().map_err(|_| unexpected())?)
+    } else if dead != "1" {
+        TaskProgress::Running
+    } else {
+        dead_status
+            .parse()
+            .map_or(TaskProgress::Killed, TaskProgress::Exited)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::{task_progress, task_script, TaskProgress};
+
+    #[test]
+    fn should_report_the_status_of_the_task() {
+        assert_eq!(
+            task_script("cargo test # all of them"),
+            "(
+cargo test # all of them
+)
+status=$?
+tmux set-option -w -t \"$TMUX_PANE\" @axl-task-status \"$status\"
+exit \"$status\""
+        );
+    }
+
+    #[rstest]
+    #[case("0||||||", TaskProgress::Running)]
+    #[case("0||||||2", TaskProgress::Exited(2))]
+    #[case("1|||0|||2", TaskProgress::Exited(2))]
+    #[case("1|||129|||", TaskProgress::Exited(129))]
+    #[case("1||||||", TaskProgress::Killed)]
+    fn should_read_task_progress(#[case] line: &str, #[case] progress: TaskProgress) {
+        assert_eq!(task_progress(line).expect("valid progress"), progress);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fmt::Display, process::Command, sync::OnceLock};
+
+use anyhow::Result;
+use strum_macros::EnumIter;
+use tracing::{debug, instrument};
+
+use super::TmuxCmd;
+use crate::{error::Error, helper::wrap_command, version::Version};
+
+/// Read once per run, `None` when the version could not be read or has no number.
+static TMUX_VERSION: OnceLock<Option<Version>> = OnceLock::new();
+
+/// Parts of tmux that `axl` uses but older releases do not have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum TmuxFeature {
+    /// `bind-key -N`, which describes a binding in `list-keys -N`.
+    KeyNotes,
+    /// `display-popup`, used for fzf pickers and key bindings.
+    Popup,
+    /// `new-session -e`, without it the variables are set after the session is created.
+    SessionEnvironment,
+    /// The `#{q:}` format modifier that shell quotes a value.
+    QuoteModifier,
+    /// Options set on a single pane with `set-option -p`.
+    PaneOptions,
+    /// The `#{client_control_mode}` format, used by hooks to skip the control mode client.
+    ControlModeFormat,
+    /// `server-access`, which decides who besides the owner may attach.
+    ServerAccess,
+}
+
+impl TmuxFeature {
+    pub const fn min_version(self) -> Version {
+        match self {
+            Self::QuoteModifier | Self::PaneOptions => Version::new(3, 0, 0),
+            Self::KeyNotes => Version::new(3, 1, 0),
+            Self::Popup | Self::SessionEnvironment | Self::ControlModeFormat => {
+                Version::new(3, 2, 0)
+            }
+            Self::ServerAccess => Version::new(3, 3, 0),
+        }
+    }
+
+    /// Whether tmux `version` has the feature, assumed to be true when the version is unknown
+    /// since that is most likely a build from source.
+    pub fn supported_by(self, version: Option<Version>) -> bool {
+        version.is_none_or(|version| version >= self.min_version())
+    }
+}
+
+impl Display for TmuxFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeyNotes => write!(f, "bind-key -N"),
+            Self::Popup => write!(f, "display-popup"),
+            Self::SessionEnvironment => write!(f, "new-session -e"),
+            Self::QuoteModifier => write!(f, "the q: format modifier"),
+            Self::PaneOptions => write!(f, "pane options"),
+            Self::ControlModeFormat => write!(f, "the client_control_mode format"),
+            Self::ServerAccess => write!(f, "server-access"),
+        }
+    }
+}
+
+impl TmuxCmd {
+    /// The version of the `tmux` executable.
+    pub fn version() -> Option<Version> {
+        *TMUX_VERSION.get_or_init(|| {
+            let output = wrap_command(Command::new(Self::CMD).arg("-V")).ok()?;
+            let version = Version::parse(&String::from_utf8_lossy(&output.stdout));
+            debug!("tmux version: {version:?}");
+            version
+        })
+    }
+
+    /// Whether `feature` can be used with the installed tmux, see [`TmuxFeature::supported_by`].
+    pub fn supports(feature: TmuxFeature) -> bool {
+        feature.supported_by(Self::version())
+    }
+
+    /// Fail with the minimum version when `feature` can not be used.
+    #[instrument(err)]
+    pub fn require(feature: TmuxFeature) -> Result<()> {
+        if let Some(version) = Self::version().filter(|_| !Self::supports(feature)) {
+            Err(Error::VersionTooOld {
+                feature: feature.to_string(),
+                tool: Self::CMD.to_string(),
+                required: feature.min_version().to_string(),
+                found: version.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    env,
+    fmt::Display,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use regex::Regex;
+use tracing::{info, instrument};
+
+use super::{version::TmuxFeature, TmuxCmd, FIELD_SEPARATOR};
+use crate::{
+    config::config_file::AxlConfig, error::Error, helper::format_duration, hooks::SESSION_NAME_KEY,
+    multiplexer::SessionName,
+};
+
+pub const WATCH_WINDOW_KEY: &str = "AXL_WATCH_WINDOW";
+pub const WATCH_MESSAGE_KEY: &str = "AXL_WATCH_MESSAGE";
+
+/// Window options holding what a window is watched for.
+const PATTERN_OPTION: &str = "@axl-watch-pattern";
+const SILENCE_OPTION: &str = "@axl-watch-silence";
+/// Pane option with the first line of output, counted from the top of the history, that has not
+/// been matched against the pattern yet.
+const SEEN_OPTION: &str = "@axl-watch-seen";
+/// Window option with `#{window_activity}` when the window was last reported quiet, tmux raises
+/// the alert again every time the silence lasts as long again.
+const QUIET_OPTION: &str = "@axl-watch-quiet";
+/// How long output has to stop for before the pattern is checked in windows that do not raise
+/// activity alerts.
+const PATTERN_CHECK_SILENCE: Duration = Duration::from_secs(2);
+
+/// The tmux alert that ran `axl watch --alert`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAlert {
+    /// The window printed something, raised by `monitor-activity`.
+    Activity,
+    /// The window printed nothing for a while, raised by `monitor-silence`.
+    Silence,
+}
+
+impl WatchAlert {
+    /// Numbered so it does not replace the user's own hook.
+    const fn hook(self) -> &'static str {
+        match self {
+            Self::Activity => "alert-activity[4202]",
+            Self::Silence => "alert-silence[4202]",
+        }
+    }
+
+    /// Session option deciding which windows raise the alert, the default leaves out the
+    /// session's current window.
+    const fn action_option(self) -> &'static str {
+        match self {
+            Self::Activity => "activity-action",
+            Self::Silence => "silence-action",
+        }
+    }
+}
+
+/// A session, or one of its windows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchTarget {
+    pub session: SessionName,
+    pub window: Option<String>,
+}
+
+impl WatchTarget {
+    /// Read `<session>` or `<session>:<window>` as typed by the user, where the window is an
+    /// index or name.
+    pub fn parse(target: &str) -> Self {
+        match target.split_once(':') {
+            Some((session, window)) if !window.is_empty() => Self {
+                session: SessionName::new(session),
+                window: Some(window.to_string()),
+            },
+            _ => Self {
+                session: SessionName::new(target.trim_end_matches(':')),
+                window: None,
+            },
+        }
+    }
+
+    fn tmux_target(&self) -> String {
+        format!(
+            "={}:{}",
+            self.session.as_str(),
+            self.window.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+impl Display for WatchTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.window {
+            Some(window) => write!(f, "{}:{window}", self.session),
+            None => write!(f, "{}", self.session),
+        }
+    }
+}
+
+/// The `capture-pane` start and end lines of output that has not been matched yet, from `seen`
+/// to the line above the cursor since the cursor line can still be written to.
+///
+/// `seen` counts from the top of the history, the returned lines from the top of the visible
+/// screen like `capture-pane` expects.
+pub fn unseen_lines(seen: u64, history_size: u64, cursor_y: u64) -> Option<(i64, i64)> {
+    let end = history_size + cursor_y;
+    (seen < end).then(|| (seen as i64 - history_size as i64, cursor_y as i64 - 1))
+}
+
+impl TmuxCmd {
+    /// Watch every window of a session, or a single one, for output stopping for `silence` or
+    /// a new line matching `pattern`. Both are raised by tmux alerts, which run
+    /// `axl watch --alert` from a global hook.
+    ///
+    /// tmux never raises activity alerts for a session's current window, so the pattern is also
+    /// checked when output stops for `silence`, or for a few seconds without it.
+    ///
+    /// Returns the ids of the watched windows.
+    #[instrument(err)]
+    pub fn watch(
+        target: &WatchTarget,
+        silence: Option<Duration>,
+        pattern: Option<&Regex>,
+    ) -> Result<Vec<String>> {
+        if pattern.is_some() {
+            Self::require(TmuxFeature::PaneOptions)?;
+        }
+        let windows = Self::target_windows(target)?;
+        let monitor_silence = silence
+            .or_else(|| pattern.map(|_| PATTERN_CHECK_SILENCE))
+            .map(|silence| silence.as_secs().max(1).to_string());
+
+        for window in &windows {
+            if let Some(seconds) = &monitor_silence {
+                Self::set_option(&["-w", "-t", window, "monitor-silence", seconds])?;
+            }
+            if let Some(silence) = silence {
+                let seconds = silence.as_secs().max(1).to_string();
+                Self::set_option(&["-w", "-t", window, SILENCE_OPTION, &seconds])?;
+            }
+            if let Some(pattern) = pattern {
+                Self::set_option(&["-w", "-t", window, "monitor-activity", "on"])?;
+                Self::set_option(&["-w", "-t", window, PATTERN_OPTION, pattern.as_str()])?;
+                // Only output printed from now on is matched.
+                for (pane_id, _, history_size, cursor_y) in Self::watched_panes(window)? {
+                    let end = (history_size + cursor_y).to_string();
+                    Self::set_option(&["-p", "-t", &pane_id, SEEN_OPTION, &end])?;
+                }
+            }
+        }
+
+        let alerts = [
+            monitor_silence.map(|_| WatchAlert::Silence),
+            pattern.map(|_| WatchAlert::Activity),
+        ];
+        for alert in alerts.into_iter().flatten() {
+            let session = format!("={}:", target.session.as_str());
+            Self::set_option(&["-t", &session, alert.action_option(), "any"])?;
+        }
+        Self::set_watch_hooks(true)?;
+        // An alert raised before watching would keep new ones from being raised.
+        Self::clear_alerts(&target.session)?;
+
+        Ok(windows)
+    }
+
+    /// Stop watching a session or window, removing the hooks once nothing is watched anymore.
+    ///
+    /// Returns the ids of the windows that are no longer watched.
+    #[instrument(err)]
+    pub fn unwatch(target: &WatchTarget) -> Result<Vec<String>> {
+        let windows = Self::target_windows(target)?;
+        for window in &windows {
+            for option in [
+                "monitor-silence",
+                "monitor-activity",
+                SILENCE_OPTION,
+                QUIET_OPTION,
+                PATTERN_OPTION,
+            ] {
+                Self::set_option(&["-wu", "-t", window, option])?;
+            }
+            if Self::supports(TmuxFeature::PaneOptions) {
+                for (pane_id, ..) in Self::watched_panes(window)? {
+                    Self::set_option(&["-pu", "-t", &pane_id, SEEN_OPTION])?;
+                }
+            }
+        }
+
+        let watched = Self::watched_sessions()?;
+        if !watched.contains(&target.session) {
+            let session = format!("={}:", target.session.as_str());
+            for alert in [WatchAlert::Activity, WatchAlert::Silence] {
+                Self::set_option(&["-u", "-t", &session, alert.action_option()])?;
+            }
+        }
+        if watched.is_empty() {
+            Self::set_watch_hooks(false)?;
+        }
+
+        Ok(windows)
+    }
+
+    /// Handle an alert raised in `window`, notifying when it is watched for it. The session's
+    /// alerts are cleared afterwards, tmux does not raise them again until they are.
+    #[instrument(skip(config), err)]
+    pub fn watch_alert(window: &str, alert: WatchAlert, config: &AxlConfig) -> Result<()> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-t")
+                .arg(window)
+                .arg("-F")
+                .arg(Self::format(&[
+                    "#{session_name}",
+                    "#{window_index}",
+                    "#{window_name}",
+                    "#{window_activity}",
+                ])),
+        )?;
+        Self::check(&output)?;
+        let line = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let [session, index, name, activity] = line.split(FIELD_SEPARATOR).collect::<Vec<_>>()[..]
+        else {
+            Err(Error::UnexpectedTmuxOutput(line))?
+        };
+        let session = SessionName::from_encoded(session);
+        let label = format!("{session}:{index} ({name})");
+
+        let silence = Self::window_option(window, SILENCE_OPTION)?;
+        let pattern = Self::window_option(window, PATTERN_OPTION)?;
+        if silence.is_empty() && pattern.is_empty() {
+            info!("{label} is not watched");
+            return Ok(());
+        }
+
+        let quiet = if alert == WatchAlert::Silence
+            && !silence.is_empty()
+            && Self::window_option(window, QUIET_OPTION)? != activity
+        {
+            Self::set_option(&["-w", "-t", window, QUIET_OPTION, activity])?;
+            let seconds = silence.parse().unwrap_or_default();
+            Some(format!(
+                "{label} has been quiet for {}",
+                format_duration(Duration::from_secs(seconds))
+            ))
+        } else {
+            None
+        };
+        let matched = if pattern.is_empty() {
+            None
+        } else {
+            Self::match_new_output(window, &Regex::new(&pattern)?)?
+                .map(|line| format!("{label} printed: {}", line.trim()))
+        };
+        for message in [matched, quiet].into_iter().flatten() {
+            Self::notify(&session, &label, &message, config)?;
+        }
+
+        Self::clear_alerts(&session)
+    }
+
+    /// Clear the alert flags of every window in `session`, which also lets tmux raise them again.
+    fn clear_alerts(session: &SessionName) -> Result<()> {
+        Self::check(&Self::run(
+            Command::new(Self::CMD)
+                .arg("kill-session")
+                .arg("-C")
+                .arg("-t")
+                .arg(format!("={}:", session.as_str())),
+        )?)
+    }
+
+    /// The last line printed in `window` since the previous check that matches `pattern`.
+    fn match_new_output(window: &str, pattern: &Regex) -> Result<Option<String>> {
+        let mut matched = None;
+        for (pane_id, seen, history_size, cursor_y) in Self::watched_panes(window)? {
+            // A cleared history starts over from the current line.
+            if let Some((start, end)) = unseen_lines(seen, history_size, cursor_y) {
+                let output = Self::run(
+                    Command::new(Self::CMD)
+                        .arg("capture-pane")
+                        .arg("-p")
+                        .arg("-J")
+                        .arg("-t")
+                        .arg(&pane_id)
+                        .arg("-S")
+                        .arg(start.to_string())
+                        .arg("-E")
+                        .arg(end.to_string()),
+                )?;
+                Self::check(&output)?;
+                if let Some(line) = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .rfind(|line| pattern.is_match(line))
+                {
+                    matched = Some(line.to_string());
+                }
+            }
+
+            let end = (history_size + cursor_y).to_string();
+            Self::set_option(&["-p", "-t", &pane_id, SEEN_OPTION, &end])?;
+        }
+        Ok(matched)
+    }
+
+    /// Run `watch.notify_command`, or show the message on every client.
+    fn notify(session: &SessionName, label: &str, message: &str, config: &AxlConfig) -> Result<()> {
+        info!("notifying: {message}");
+        if let Some(command) = &config.watch.notify_command {
+            let status = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .env(SESSION_NAME_KEY, session.as_str())
+                .env(WATCH_WINDOW_KEY, label)
+                .env(WATCH_MESSAGE_KEY, message)
+                .stdin(Stdio::null())
+                .status()?;
+            if !status.success() {
+                Err(Error::HookFailed(command.clone(), status.to_string()))?;
+            }
+            return Ok(());
+        }
+
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-clients")
+                .arg("-F")
+                .arg("#{client_name}"),
+        )?;
+        for client in String::from_utf8_lossy(&output.stdout).lines() {
+            // Messages are expanded as formats.
+            Self::check(&Self::run(
+                Command::new(Self::CMD)
+                    .arg("display-message")
+                    .arg("-c")
+                    .arg(client)
+                    .arg(format!("axl: {}", message.replace('#', "##"))),
+            )?)?;
+        }
+        Ok(())
+    }
+
+    /// Ids of the windows a target points at.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    fn target_windows(target: &WatchTarget) -> Result<Vec<String>> {
+        let command = if target.window.is_some() {
+            "list-panes"
+        } else {
+            "list-windows"
+        };
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg(command)
+                .arg("-t")
+                .arg(target.tmux_target())
+                .arg("-F")
+                .arg("#{window_id}"),
+        )?;
+        Self::check(&output)?;
+
+        let mut windows: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(ToString::to_string)
+            .collect();
+        windows.dedup();
+        Ok(windows)
+    }
+
+    /// Id, first unseen line, history size and cursor line of each pane in `window`.
+    fn watched_panes(window: &str) -> Result<Vec<(String, u64, u64, u64)>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-panes")
+                .arg("-t")
+                .arg(window)
+                .arg("-F")
+                .arg(Self::format(&[
+                    "#{pane_id}",
+                    &format!("#{{{SEEN_OPTION}}}"),
+                    "#{history_size}",
+                    "#{cursor_y}",
+                ])),
+        )?;
+        Self::check(&output)?;
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| {
+                let unexpected = || Error::UnexpectedTmuxOutput(line.to_string());
+                let [pane_id, seen, history_size, cursor_y] =
+                    line.split(FIELD_SEPARATOR).collect::<Vec<_>>()[..]
+                else {
+                    Err(unexpected())?
+                };
+                let history_size = history_size.parse().map_err(|_| unexpected())?;
+                let cursor_y = cursor_y.parse().map_err(|_| unexpected())?;
+                Ok((
+                    pane_id.to_string(),
+                    seen.parse().unwrap_or(history_size + cursor_y),
+                    history_size,
+                    cursor_y,
+                ))
+            })
+            .collect()
+    }
+
+    /// Sessions with at least one watched window.
+    fn watched_sessions() -> Result<Vec<SessionName>> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("list-windows")
+                .arg("-a")
+                .arg("-F")
+                .arg(Self::format(&[
+                    "#{session_name}",
+                    &format!("#{{{SILENCE_OPTION}}}#{{!=:#{{{PATTERN_OPTION}}},}}"),
+                ])),
+        )?;
+        Self::check(&output)?;
+
+        let mut sessions: Vec<SessionName> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+            .filter(|(_, watched)| *watched != "0")
+            .map(|(session, _)| SessionName::from_encoded(session))
+            .collect();
+        sessions.dedup();
+        Ok(sessions)
+    }
+
+    fn window_option(window: &str, option: &str) -> Result<String> {
+        let output = Self::run(
+            Command::new(Self::CMD)
+                .arg("show-options")
+                .arg("-wqv")
+                .arg("-t")
+                .arg(window)
+                .arg(option),
+        )?;
+        Self::check(&output)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string())
+    }
+
+    fn set_option(args: &[&str]) -> Result<()> {
+        Self::check(&Self::run(
+            Command::new(Self::CMD).arg("set-option").args(args),
+        )?)
+    }
+
+    /// Install (or remove) the global hooks that pass alerts on to `axl watch --alert`.
+    #[allow(clippy::literal_string_with_formatting_args)] // A tmux format, not a format arg.
+    fn set_watch_hooks(enabled: bool) -> Result<()> {
+        for alert in [WatchAlert::Activity, WatchAlert::Silence] {
+            let mut command = Command::new(Self::CMD);
+            if enabled {
+                let name = alert
+                    .to_possible_value()
+                    .map_or_else(String::new, |v| v.get_name().to_string());
+                command
+                    .arg("set-hook")
+                    .arg("-g")
+                    .arg(alert.hook())
+                    .arg(format!(
+                    "run-shell -b \"'{}' --decoration none watch '#{{window_id}}' --alert {name}\"",
+                    env::current_exe()?.to_string_lossy()
+                ));
+            } else {
+                command.arg("set-hook").arg("-gu").arg(alert.hook());
+            }
+            Self::check(&Self::run(&mut command)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::{unseen_lines, WatchTarget};
+    use crate::multiplexer::SessionName;
+
+    #[rstest]
+    #[case(100, 100, 5, Some((0, 4)))]
+    #[case(98, 100, 5, Some((-2, 4)))]
+    #[case(105, 100, 5, None)]
+    #[case(0, 0, 0, None)]
+    #[case(200, 100, 5, None)]
+    fn should_find_unseen_lines(
+        #[case] seen: u64,
+        #[case] history_size: u64,
+        #[case] cursor_y: u64,
+        #[case] expected: Option<(i64, i64)>,
+    ) {
+        assert_eq!(unseen_lines(seen, history_size, cursor_y), expected);
+    }
+
+    #[rstest]
+    #[case("api", "api", None, "=api:")]
+    #[case("api:", "api", None, "=api:")]
+    #[case("api:2", "api", Some("2"), "=api:2")]
+    #[case("api.v2:build", "api.v2", Some("build"), "=api%2Ev2:build")]
+    fn should_parse_watch_target(
+        #[case] target: &str,
+        #[case] session: &str,
+        #[case] window: Option<&str>,
+        #[case] tmux_target: &str,
+    ) {
+        let parsed = WatchTarget::parse(target);
+
+        assert_eq!(parsed.session, SessionName::new(session));
+        assert_eq!(parsed.window.as_deref(), window);
+        assert_eq!(parsed.tmux_target(), tmux_target);
+    }
+}
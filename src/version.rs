@@ -0,0 +1,81 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A release of an external tool like tmux or fzf, compared to find out what it supports.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The first version number in the output of `tmux -V` or `fzf --version`. Letter suffixes
+    /// (`3.3a`) and pre-releases (`3.2-rc2`, `next-3.4`) count as the release they lead up to.
+    ///
+    /// `None` when there is no number, like the `tmux master` of a source build.
+    pub fn parse(output: &str) -> Option<Self> {
+        let number = output
+            .split_whitespace()
+            .map(|word| word.strip_prefix("next-").unwrap_or(word))
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+
+        let mut parts = number
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?
+            .split('.')
+            .map(|part| part.parse().unwrap_or_default());
+
+        Some(Self::new(
+            parts.next()?,
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or_default(),
+        ))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch > 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use similar_asserts::assert_eq;
+
+    use super::Version;
+
+    #[rstest]
+    #[case("tmux 3.3a", Some(Version::new(3, 3, 0)))]
+    #[case("tmux 3.2-rc2", Some(Version::new(3, 2, 0)))]
+    #[case("tmux next-3.5", Some(Version::new(3, 5, 0)))]
+    #[case("tmux master", None)]
+    #[case("0.44.1 (d7d2ac3)", Some(Version::new(0, 44, 1)))]
+    #[case("0.53.0 (brew)", Some(Version::new(0, 53, 0)))]
+    fn should_parse_version_output(#[case] output: &str, #[case] expected: Option<Version>) {
+        assert_eq!(Version::parse(output), expected);
+    }
+
+    #[test]
+    fn should_compare_versions() {
+        assert!(Version::new(0, 44, 1) < Version::new(0, 53, 0));
+        assert!(Version::new(3, 3, 0) >= Version::new(3, 2, 0));
+        assert_eq!(Version::new(3, 2, 0).to_string(), "3.2");
+        assert_eq!(Version::new(0, 53, 1).to_string(), "0.53.1");
+    }
+}
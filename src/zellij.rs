@@ -0,0 +1,251 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::{
+    config::config_file::AxlConfig,
+    error::Error,
+    helper::wrap_command,
+    history::SessionHistory,
+    multiplexer::{Multiplexer, SessionName},
+};
+
+#[derive(Debug)]
+pub struct ZellijCmd;
+
+impl Multiplexer for ZellijCmd {
+    /// Layouts and hooks are tmux specific, so only the session path is used from the project
+    /// config.
+    #[instrument(skip(_config), err)]
+    fn open(path: &Path, name: &SessionName, _config: &AxlConfig) -> Result<()> {
+        info!(
+            "Attempting to open Zellij session with path: {:?}, name: {:?}!",
+            path, name,
+        );
+
+        if !path.exists() {
+            Err(Error::PathDoesNotExist(path.to_string_lossy().to_string()))?;
+        }
+
+        if !Self::in_session() {
+            SessionHistory::record(name);
+            Self::attach(name.as_str(), Some(path))?;
+        } else if Self::get_current_session() != *name {
+            // zellij can not move a running client to another session from the command line, so
+            // the best we can do is make sure the session is there to attach to later.
+            if !Self::has_session(name)
+                && !Self::create_new_detached(name.as_str(), path).is_ok_and(|o| o.status.success())
+            {
+                eprintln!("{}", "Session failed to open.".red().bold());
+                Err(Error::CouldNotCreateSession)?;
+            }
+            Err(Error::CannotSwitchSession(name.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(_config), err)]
+    fn open_existing(name: &SessionName, _config: &AxlConfig) -> Result<()> {
+        info!(
+            "Attempting to open existing Zellij session with name: {:?}!",
+            name,
+        );
+
+        if !Self::in_session() {
+            SessionHistory::record(name);
+        }
+        Self::switch(name)
+    }
+
+    /// zellij can not move a running client to another session from the command line, so from
+    /// inside zellij this only succeeds for the current session.
+    #[instrument(err)]
+    fn switch(name: &SessionName) -> Result<()> {
+        if Self::in_session() {
+            if Self::get_current_session() != *name {
+                Err(Error::CannotSwitchSession(name.to_string()))?;
+            }
+            return Ok(());
+        }
+
+        Self::attach(name.as_str(), None)
+    }
+
+    #[instrument]
+    fn list_sessions() -> Result<Vec<SessionName>> {
+        Ok(String::from_utf8_lossy(
+            &wrap_command(
+                Command::new(Self::CMD)
+                    .arg("list-sessions")
+                    .arg("--short")
+                    .arg("--no-formatting"),
+            )?
+            .stdout,
+        )
+        .trim_end()
+        .split('\n')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(SessionName::from_encoded)
+        .collect())
+    }
+
+    /// zellij does not report where a session was started.
+    #[instrument]
+    fn list_session_paths() -> Result<Vec<(SessionName, Option<PathBuf>)>> {
+        Ok(Self::list_sessions()?
+            .into_iter()
+            .map(|name| (name, None))
+            .collect())
+    }
+
+    #[instrument]
+    fn get_current_session() -> SessionName {
+        SessionName::from_encoded(&env::var("ZELLIJ_SESSION_NAME").unwrap_or_default())
+    }
+
+    #[instrument(skip(_config), err)]
+    fn kill_sessions(
+        sessions: &[SessionName],
+        current_session: &SessionName,
+        _config: &AxlConfig,
+    ) -> Result<Vec<SessionName>> {
+        Ok(Self::kill_each(sessions, current_session, |s| {
+            Self::kill_session(s.as_str())
+        }))
+    }
+
+    #[instrument]
+    fn has_session(name: &SessionName) -> bool {
+        Self::list_sessions().is_ok_and(|sessions| sessions.iter().any(|s| s == name))
+    }
+}
+
+impl ZellijCmd {
+    const CMD: &'static str = "zellij";
+
+    #[instrument(err)]
+    fn create_new_detached(name: &str, path: &Path) -> Result<Output> {
+        wrap_command(
+            Command::new(Self::CMD)
+                .current_dir(path)
+                .arg("attach")
+                .arg("--create-background")
+                .arg(name),
+        )
+    }
+
+    /// Attaching needs the terminal, so the output is not captured like other commands.
+    #[instrument(err)]
+    fn attach(name: &str, create_in: Option<&Path>) -> Result<()> {
+        let mut command = Command::new(Self::CMD);
+        command.arg("attach");
+        if let Some(path) = create_in {
+            command.current_dir(path).arg("--create");
+        }
+        if !command.arg(name).status()?.success() {
+            Err(Error::CouldNotCreateSession)?;
+        }
+        Ok(())
+    }
+
+    /// Kill every session in `sessions` with `kill`, `current_session` last, returning the ones
+    /// that were killed.
+    fn kill_each(
+        sessions: &[SessionName],
+        current_session: &SessionName,
+        mut kill: impl FnMut(&SessionName) -> Result<()>,
+    ) -> Vec<SessionName> {
+        let mut killed = Vec::new();
+        for s in sessions.iter().filter(|s| *s != current_session) {
+            if kill(s).is_ok() {
+                if s.as_str().is_empty() {
+                    warn!("No session picked");
+                } else {
+                    info!("Killed {}.", s);
+                    killed.push(s.clone());
+                }
+            } else {
+                error!("Error while killing {}.", s)
+            }
+        }
+
+        if sessions.contains(current_session) {
+            debug!("current session [{current_session}] was included to be killed.");
+
+            if kill(current_session).is_ok() {
+                info!("Killed {current_session}.");
+                killed.push(current_session.clone());
+            } else {
+                error!("Error while killing {current_session}.")
+            }
+        }
+
+        killed
+    }
+
+    #[instrument(err)]
+    fn kill_session(name: &str) -> Result<()> {
+        Self::check(&wrap_command(
+            Command::new(Self::CMD).arg("kill-session").arg(name),
+        )?)
+    }
+
+    fn check(output: &Output) -> Result<()> {
+        if !output.status.success() {
+            Err(Error::ZellijCommandFailed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))?;
+        }
+        Ok(())
+    }
+
+    #[instrument]
+    fn in_session() -> bool {
+        env::var("ZELLIJ").is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        os::unix::process::ExitStatusExt,
+        process::{ExitStatus, Output},
+    };
+
+    use similar_asserts::assert_eq;
+
+    use super::ZellijCmd;
+    use crate::multiplexer::SessionName;
+
+    #[test]
+    fn should_only_report_sessions_zellij_killed() {
+        let output = |code: i32| Output {
+            // Raw wait status.
+            status: ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: b"Session not found".to_vec(),
+        };
+        let sessions = [
+            SessionName::new("api"),
+            SessionName::new("docs"),
+            SessionName::new("web"),
+        ];
+
+        let killed = ZellijCmd::kill_each(&sessions, &SessionName::new("web"), |s| {
+            ZellijCmd::check(&output(i32::from(s.as_str() == "docs")))
+        });
+
+        assert_eq!(
+            killed,
+            vec![SessionName::new("api"), SessionName::new("web")]
+        );
+        assert!(ZellijCmd::check(&output(1)).is_err());
+    }
+}
@@ -5,7 +5,7 @@ use std::{
 };
 use tracing::instrument;
 
-use crate::helper::wrap_command;
+use crate::{fzf::FzfCmd, helper::wrap_command};
 
 #[derive(Debug)]
 pub struct ZoxideCmd;
@@ -25,10 +25,11 @@ impl ZoxideCmd {
 
     #[instrument()]
     pub fn query_interactive(input: &str) -> anyhow::Result<PathBuf> {
-        let fzf_opts = [
-            "--tmux",                    // start fzf in a tmux popup
-            &format!("--query={input}"), // start fzf with the query pre filled
-        ];
+        let mut fzf_opts: Vec<String> = FzfCmd::popup_args()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        fzf_opts.push(format!("--query={input}")); // start fzf with the query pre filled
 
         let zoxide_child = Command::new(Self::CMD)
             .env("_ZO_FZF_OPTS", fzf_opts.join(" "))
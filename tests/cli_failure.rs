@@ -3,7 +3,7 @@ use std::env;
 use assert_cmd::Command;
 use rstest::rstest;
 
-use predicates::prelude::predicate;
+use predicates::prelude::{predicate, PredicateBooleanExt};
 
 // Make some snapshot assertions on command output
 
@@ -34,3 +34,35 @@ fn axl_project_no_args(
 
     Ok(())
 }
+
+#[rstest]
+#[case::from_config(vec![], true)]
+#[case::overridden_by_flag(vec!["--on-error", "none"], false)]
+fn axl_on_error_from_config(
+    #[case] args: Vec<&str>,
+    #[case] pauses: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Arrange
+    let dir = assert_fs::TempDir::new()?;
+    let config_path = dir.path().join("config.yml");
+    std::fs::write(
+        &config_path,
+        "general:\n  decoration: none\n  on_error: pause\n",
+    )?;
+    let mut cmd = Command::cargo_bin("axl")?;
+    cmd.env("AXL_CONFIG_PATH", &config_path)
+        .env("XDG_DATA_HOME", dir.path())
+        .args(args)
+        .args(["history", "missing"]);
+
+    // Act / Assert
+    // Pausing prompts for ENTER, which fails without a terminal.
+    let not_a_tty = predicate::str::contains("not a TTY");
+    if pauses {
+        cmd.assert().failure().stderr(not_a_tty);
+    } else {
+        cmd.assert().failure().stderr(not_a_tty.not());
+    }
+
+    Ok(())
+}